@@ -46,7 +46,7 @@
 //! BUG 3 — rasciichart's with_width(W) is the PLOT area width. The library
 //!          then prepends Y-axis labels (~10–12 chars wide) making the total
 //!          line longer than the terminal → horizontal wrap / garbled output.
-//!          FIX: reserve LABEL_RESERVE chars (12) so total line fits the terminal.
+//!          FIX: reserve LABEL_RESERVE chars (13) so total line fits the terminal.
 //!
 //! BUG 4 — EnterAlternateScreen + resize_with(term_height, String::new) padded
 //!          every frame with dozens of bare newlines, scrolling the screen.
@@ -55,31 +55,69 @@
 //!
 //! ─────────────────────────────────────────────────────────────────────────────
 
+mod capture;
+mod check;
+mod color;
+mod config;
+mod daemon;
+mod export;
+mod filter;
+mod iface;
+mod influx;
+mod link;
+mod measure;
+mod netns;
+mod output;
+mod packet;
+mod pcap;
+mod procs;
+mod prometheus;
+mod push;
+mod report;
+mod route;
+mod sampler;
+mod select;
+mod session;
+mod store;
+// Shared with bandwidthmon2/3, which also use its print loop; only the
+// column layout is used here.
+#[allow(dead_code)]
+mod textlog;
+mod view;
+
 use anyhow::{Context, Result};
-use clap::{Parser, ArgAction};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ArgAction};
 use rasciichart::{plot_with_config, Config};
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
-use std::io::{stdout, Write};
+use std::io::{stdout, IsTerminal, Write};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use sysinfo::Networks;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use color::ColorMode;
+use output::OutputFormat;
+use sampler::Sampler;
 
-// crossterm is used ONLY for raw-mode keyboard input — NOT for rendering.
+// crossterm is used ONLY for raw-mode keyboard and mouse input — NOT for rendering.
 use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+    execute,
     terminal::{disable_raw_mode, enable_raw_mode},
 };
 
 // ── Constants ─────────────────────────────────────────────────────────────────
 
 const INTERVAL: Duration = Duration::from_secs(1);
+/// How long auto-selection measures when there is no default route.
+const PROBE: Duration = Duration::from_millis(500);
 const DEFAULT_HISTORY: usize = 120;
 const DEFAULT_HEIGHT: usize = 10;
 
-/// Chars reserved for rasciichart's Y-axis label column (e.g. "725498.4│ ").
-const LABEL_RESERVE: usize = 12;
+/// Chars reserved for rasciichart's Y-axis label column (e.g. "104857600.0│"),
+/// plus one so a full-width chart line never lands in the last column.
+const LABEL_RESERVE: usize = 13;
 
 /// Never pass a plot width smaller than this to rasciichart.
 const MIN_PLOT_WIDTH: usize = 8;
@@ -98,7 +136,9 @@ const MAX_PLOT_WIDTH: usize = 400;
 // ── Colour helpers ────────────────────────────────────────────────────────────
 
 fn styled(text: &str, col: u8, bold: bool) -> String {
-    if bold {
+    if !color::enabled() {
+        text.to_string()
+    } else if bold {
         format!("\x1b[1m\x1b[38;5;{}m{}\x1b[0m", col, text)
     } else {
         format!("\x1b[38;5;{}m{}\x1b[0m", col, text)
@@ -110,6 +150,7 @@ const C_YELLOW: u8 = 226;
 const C_WHITE:  u8 = 15;
 const C_GREY:   u8 = 240;
 const C_GREEN:  u8 = 46;
+const C_RED:    u8 = 196;
 
 // ── Terminal width (BUG 1 fix) ────────────────────────────────────────────────
 
@@ -119,8 +160,7 @@ fn term_cols() -> usize {
     term_size::dimensions()
         .map(|(w, _)| w)
         .unwrap_or(80)
-        .max(40)   // sanity floor
-        .min(500)  // sanity ceiling
+        .clamp(40, 500)  // sanity floor / ceiling
 }
 
 // ── Version string ────────────────────────────────────────────────────────────
@@ -144,123 +184,243 @@ impl fmt::Display for ColoredVersion {
     disable_version_flag = true
 )]
 struct Args {
-    /// Network interface to monitor (auto-select if not specified)
+    /// Interface to monitor: a name, a glob such as 'eth*' or 're:REGEX' to watch
+    /// every match together (auto-select if not specified)
     #[arg(short, long)]
     iface: Option<String>,
 
+    /// Never pick or list interfaces matching PATTERN (name, glob or re:REGEX; repeatable)
+    #[arg(long, value_name = "PATTERN", value_parser = select::Pattern::parse)]
+    exclude: Vec<select::Pattern>,
+
     /// Chart height in lines
-    #[arg(short = 'H', long, default_value_t = DEFAULT_HEIGHT)]
+    #[arg(short = 'H', long, global = true, default_value_t = DEFAULT_HEIGHT)]
     height: usize,
 
     /// Chart width in columns (0 = auto-fit terminal)
-    #[arg(short = 'W', long, default_value_t = 0)]
+    #[arg(short = 'W', long, global = true, default_value_t = 0)]
     width: usize,
 
     /// List available network interfaces and exit
     #[arg(short, long)]
     list: bool,
 
+    /// With --list: print JSON (same as --output jsonl)
+    #[arg(long, requires = "list")]
+    json: bool,
+
+    /// With --list: order by this column (counters and speed descending)
+    #[arg(long, value_enum, default_value_t = iface::SortKey::Name, requires = "list")]
+    sort: iface::SortKey,
+
+    /// With --list: only these interface types, comma-separated
+    #[arg(long, value_enum, value_delimiter = ',', requires = "list")]
+    kind: Vec<iface::Kind>,
+
+    /// With --list: only interfaces that are up
+    #[arg(long, requires = "list")]
+    up: bool,
+
     /// Show peak / average / total summary
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     summary: bool,
 
     /// Show download chart only
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     download: bool,
 
     /// Show upload chart only
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     upload: bool,
 
     /// Maximum number of history samples to keep
-    #[arg(long, default_value_t = DEFAULT_HISTORY)]
+    #[arg(long, global = true, default_value_t = DEFAULT_HISTORY)]
     history: usize,
 
+    /// Output mode: live charts, or one line per interval for pipes
+    /// (`human` falls back to `text` when stdout is not a terminal)
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Human)]
+    output: OutputFormat,
+
+    /// Link speed for utilisation, in bits/s: 1G, 2.5Gbit, 300M (default: what the kernel reports)
+    #[arg(long, value_name = "SPEED", value_parser = link::parse_speed)]
+    link_speed: Option<f64>,
+
+    /// Show rx / tx link utilisation gauges under the speeds
+    #[arg(long, global = true)]
+    gauge: bool,
+
+    /// Fixed chart maximum: `link` for the link speed, or a rate such as 100MB/s
+    #[arg(long, value_name = "MAX", global = true, value_parser = link::YMax::parse)]
+    y_max: Option<link::YMax>,
+
+    /// Show the top N processes by traffic under the charts (Linux; N defaults to 10)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = procs::DEFAULT_TOP.to_string())]
+    processes: Option<usize>,
+
+    /// Capture packets and show the top N talkers under the charts (Linux, root; N defaults to 10)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = procs::DEFAULT_TOP.to_string())]
+    capture: Option<usize>,
+
+    /// With --capture: only count packets matching EXPR, e.g. "port 443", "tcp and host 10.0.0.5"
+    #[arg(long, value_name = "EXPR", value_parser = filter::Filter::parse, requires = "capture")]
+    filter: Option<filter::Filter>,
+
+    /// Watch the network namespace of process PID (e.g. a container's)
+    #[arg(long, value_name = "PID", conflicts_with_all = ["netns", "processes", "capture"])]
+    pid: Option<u32>,
+
+    /// Watch the named network namespace NAME (`ip netns`; needs root)
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["processes", "capture"])]
+    netns: Option<String>,
+
+    /// Group --capture talkers by flow or by remote host
+    #[arg(long, value_enum, default_value_t = capture::Group::Flow)]
+    group: capture::Group,
+
+    /// Leave the mouse to the terminal (text selection) instead of the charts
+    #[arg(long, global = true)]
+    no_mouse: bool,
+
+    /// Colourise output: auto (terminal and no NO_COLOR), always or never
+    #[arg(long, global = true, value_enum, default_value_t = ColorMode::Auto, value_name = "WHEN")]
+    color: ColorMode,
+
+    /// Field delimiter for `--output csv`: ',', ';', '|' or 'tab'
+    #[arg(long, default_value = ",", value_parser = output::parse_delimiter)]
+    delimiter: char,
+
+    /// Measure quietly for --duration, print one summary and exit
+    #[arg(long)]
+    once: bool,
+
+    /// How long --once measures (e.g. 500ms, 10s, 2m)
+    #[arg(long, default_value = "10s", value_parser = measure::parse_duration, requires = "once")]
+    duration: Duration,
+
+    /// With --once: exit 1 (WARNING) if CONDITION holds, e.g. "avg_rx > 50MB/s" (repeatable)
+    #[arg(long, value_name = "CONDITION", value_parser = check::Condition::parse, requires = "once")]
+    warn_if: Vec<check::Condition>,
+
+    /// With --once: exit 2 (CRITICAL) if CONDITION holds, e.g. "peak_tx < 1KB/s" (repeatable)
+    #[arg(long, value_name = "CONDITION", value_parser = check::Condition::parse, requires = "once")]
+    fail_if: Vec<check::Condition>,
+
+    /// With --once: print a Nagios/Icinga plugin status line with perfdata
+    #[arg(long, requires = "once")]
+    nagios: bool,
+
+    /// Append every sample to the persistent history (see `report`)
+    #[arg(long)]
+    persist: bool,
+
+    /// Save every sample of this session to FILE (play back with `replay`)
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// History directory (default: ~/.local/share/bandwidthmon)
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    export: export::ExportArgs,
+
+    /// Config file (default: ~/.config/bandwidthmon/config.toml)
+    #[arg(long, global = true, value_name = "FILE", env = "BANDWIDTHMON_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Apply the [profiles.NAME] table from the config file
+    #[arg(short = 'P', long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Print the effective settings (config + profile + flags) as TOML and exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// Display names and colours from the config file
+    #[arg(skip)]
+    display: config::Display,
+
     #[arg(short = 'v', long = "version", action = ArgAction::SetTrue)]
     version: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Args {
+    fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone().unwrap_or_else(store::default_data_dir)
+    }
+
+    /// The namespace picked by `--pid` / `--netns`, if any.
+    fn namespace(&self) -> Option<netns::Namespace> {
+        netns::Namespace::from_args(self.pid, self.netns.as_deref())
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print hourly / daily / monthly traffic tables from stored history
+    Report(report::ReportArgs),
+
+    /// Sample headlessly into the history store (for systemd / services)
+    #[command(visible_alias = "daemon")]
+    Record(daemon::RecordArgs),
+
+    /// Play back a file saved with --record in the live charts
+    Replay(session::ReplayArgs),
+
+    /// Chart bandwidth over time from a pcap / pcapng capture file
+    Pcap(pcap::PcapArgs),
 }
 
 // ── Bandwidth stats ───────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct BandwidthStats {
     download_bps: f64,
     upload_bps:   f64,
     total_rx:     u64,
     total_tx:     u64,
+    rx_delta:     u64,   // bytes since the previous sample
+    tx_delta:     u64,
+    elapsed:      f64,   // seconds since the previous sample
+    rx_packets:   u64,   // cumulative; 0 where the platform doesn't report them
+    tx_packets:   u64,
+    rx_errors:    u64,
+    tx_errors:    u64,
 }
 
 // ── Network monitor ───────────────────────────────────────────────────────────
 
-struct NetworkMonitor {
-    interface:    String,
-    networks:     Networks,
+/// History and running statistics of one interface.
+#[derive(Default)]
+struct Track {
     history_dl:   VecDeque<f64>,
     history_ul:   VecDeque<f64>,
-    history_size: usize,           // explicit cap — don't rely on capacity()
-    prev_rx:      u64,
-    prev_tx:      u64,
-    prev_time:    Instant,
-    start_time:   Instant,
+    history_ts:   VecDeque<i64>,   // unix ms of each history sample
     peak_dl:      f64,
     peak_ul:      f64,
     avg_dl:       f64,
     avg_ul:       f64,
     sample_count: u64,
+    session_rx:   u64,             // bytes since start / last reset
+    session_tx:   u64,
 }
 
-impl NetworkMonitor {
-    fn new(interface: String, history_size: usize) -> Result<Self> {
-        let networks = Networks::new_with_refreshed_list();
-        if !networks.iter().any(|(n, _)| n == &interface) {
-            anyhow::bail!("Interface '{}' not found", interface);
-        }
-        let (prev_rx, prev_tx) = networks.get(&interface)
-            .map(|d| (d.total_received(), d.total_transmitted()))
-            .unwrap_or((0, 0));
-        let now = Instant::now();
-        Ok(Self {
-            interface, networks,
-            history_dl:   VecDeque::with_capacity(history_size + 1),
-            history_ul:   VecDeque::with_capacity(history_size + 1),
-            history_size,
-            prev_rx, prev_tx,
-            prev_time: now, start_time: now,
-            peak_dl: 0.0, peak_ul: 0.0,
-            avg_dl:  0.0, avg_ul:  0.0,
-            sample_count: 0,
-        })
-    }
-
-    fn update(&mut self) -> Result<BandwidthStats> {
-        self.networks.refresh(false);
-        let data = self.networks.get(&self.interface)
-            .context("Interface disappeared")?;
-
-        let cur_rx   = data.total_received();
-        let cur_tx   = data.total_transmitted();
-        let cur_time = Instant::now();
-        let elapsed  = cur_time.duration_since(self.prev_time).as_secs_f64();
-
-        if elapsed < 0.001 {
-            return Ok(BandwidthStats {
-                download_bps: 0.0, upload_bps: 0.0,
-                total_rx: cur_rx,  total_tx:  cur_tx,
-            });
-        }
-
-        let dl_bps = cur_rx.saturating_sub(self.prev_rx) as f64 / elapsed;
-        let ul_bps = cur_tx.saturating_sub(self.prev_tx) as f64 / elapsed;
-
-        self.prev_rx   = cur_rx;
-        self.prev_tx   = cur_tx;
-        self.prev_time = cur_time;
+impl Track {
+    /// Fold one sample, taken at `ts` (unix ms), into history, peak and average.
+    fn push(&mut self, stats: &BandwidthStats, history_size: usize, ts: i64) {
+        let (dl_bps, ul_bps) = (stats.download_bps, stats.upload_bps);
 
         // History: compare against stored history_size (not .capacity()).
-        if self.history_dl.len() >= self.history_size { self.history_dl.pop_front(); }
+        if self.history_dl.len() >= history_size { self.history_dl.pop_front(); }
         self.history_dl.push_back(dl_bps);
-        if self.history_ul.len() >= self.history_size { self.history_ul.pop_front(); }
+        if self.history_ul.len() >= history_size { self.history_ul.pop_front(); }
         self.history_ul.push_back(ul_bps);
+        if self.history_ts.len() >= history_size { self.history_ts.pop_front(); }
+        self.history_ts.push_back(ts);
 
         // Welford online mean + running peak.
         self.peak_dl      = self.peak_dl.max(dl_bps);
@@ -268,15 +428,189 @@ impl NetworkMonitor {
         self.sample_count += 1;
         self.avg_dl       += (dl_bps - self.avg_dl) / self.sample_count as f64;
         self.avg_ul       += (ul_bps - self.avg_ul) / self.sample_count as f64;
+        self.session_rx   += stats.rx_delta;
+        self.session_tx   += stats.tx_delta;
+    }
 
-        Ok(BandwidthStats {
-            download_bps: dl_bps, upload_bps: ul_bps,
-            total_rx: cur_rx,     total_tx:  cur_tx,
-        })
+    /// Start peak, average and session totals over; history stays.
+    fn reset(&mut self) {
+        let (dl, ul) = (std::mem::take(&mut self.history_dl), std::mem::take(&mut self.history_ul));
+        let ts = std::mem::take(&mut self.history_ts);
+        *self = Self { history_dl: dl, history_ul: ul, history_ts: ts, ..Self::default() };
+    }
+}
+
+struct NetworkMonitor {
+    interface:    String,
+    sampler:      Option<Sampler>,                // None when replaying a file
+    latest:       Vec<(String, BandwidthStats)>,  // last sample of every interface
+    track:        Track,                          // the monitored interface
+    others:       HashMap<String, Track>,         // every other interface, for switching
+    group:        Option<select::Group>,          // -i glob / regex, summed into `latest`
+    device:       Option<(select::Device, String)>, // -i mac: / ip: / altname, and its name now
+    gone:         bool,                           // the device was missing from the last sample
+    renamed:      Option<(String, String)>,       // the device's new name in the last sample
+    history_size: usize,           // explicit cap — don't rely on capacity()
+    start_time:   Instant,
+    replay_clock: f64,                            // summed sample time (replay only)
+}
+
+impl NetworkMonitor {
+    fn new(target: select::Target, history_size: usize, ns: Option<&netns::Namespace>) -> Result<Self> {
+        let sampler = Sampler::open(ns)?;
+        let (group, device) = match target {
+            select::Target::Group(ref g) => (Some(g.clone()), None),
+            select::Target::Interface(ref name) | select::Target::Device(_, ref name) if !sampler.contains(name) => {
+                anyhow::bail!("Interface '{}' not found", name);
+            }
+            select::Target::Interface(_)            => (None, None),
+            select::Target::Device(ref d, ref name) => (None, Some((d.clone(), name.clone()))),
+        };
+        Ok(Self { sampler: Some(sampler), group, device, ..Self::detached(target.name().to_string(), history_size) })
+    }
+
+    /// A monitor with no live counters, fed through `push` (replay).
+    fn detached(interface: String, history_size: usize) -> Self {
+        Self {
+            interface,
+            sampler:      None,
+            latest:       Vec::new(),
+            track:        Track::default(),
+            others:       HashMap::new(),
+            group:        None,
+            device:       None,
+            gone:         false,
+            renamed:      None,
+            history_size,
+            start_time:   Instant::now(),
+            replay_clock: 0.0,
+        }
+    }
+
+    /// Sample every interface; returns the monitored one.
+    fn update(&mut self) -> Result<BandwidthStats> {
+        let sampler = self.sampler.as_mut().context("No live counters while replaying")?;
+        let before = std::mem::replace(&mut self.latest, sampler.sample());
+        self.follow_device(&before);
+        if let Some(ref g) = self.group {
+            self.latest.insert(0, (g.name.clone(), g.sum(&self.latest)));
+        }
+        let stats = self.current().cloned().context("Interface disappeared")?;
+        let now = chrono::Local::now().timestamp_millis();
+        for (name, s) in &self.latest {
+            if name != &self.interface && s.elapsed >= 0.001 {
+                self.others.entry(name.clone()).or_default().push(s, self.history_size, now);
+            }
+        }
+        self.push(&stats, now);
+        Ok(stats)
+    }
+
+    /// `-i mac:…` and friends: look the device up again, and follow it to a
+    /// new name. While it is gone its entry carries on with no traffic.
+    fn follow_device(&mut self, before: &[(String, BandwidthStats)]) {
+        self.renamed = None;
+        let Some((ref dev, ref name)) = self.device else { return };
+        let found = self.sampler.as_ref().and_then(|s| s.find(dev));
+        let name = name.clone();
+        self.gone = found.is_none();
+        match found {
+            Some(now) if now == name => {}
+            Some(now) => {
+                if self.interface == name {
+                    self.others.remove(&now);
+                    self.interface = now.clone();
+                } else if let Some(track) = self.others.remove(&name) {
+                    self.others.insert(now.clone(), track);
+                }
+                if let Some((_, ref mut n)) = self.device { *n = now.clone(); }
+                self.renamed = Some((name, now));
+            }
+            None => {
+                let elapsed = self.latest.first().map(|(_, s)| s.elapsed).unwrap_or_default();
+                let last = before.iter().find(|(n, _)| *n == name).map(|(_, s)| s.clone()).unwrap_or_default();
+                let idle = BandwidthStats { download_bps: 0.0, upload_bps: 0.0, rx_delta: 0, tx_delta: 0, elapsed, ..last };
+                // The name may have gone to another device meanwhile.
+                self.latest.retain(|(n, _)| *n != name);
+                let at = self.latest.partition_point(|(n, _)| *n < name);
+                self.latest.insert(at, (name, idle));
+            }
+        }
     }
 
-    fn dl_history(&self) -> Vec<f64> { self.history_dl.iter().copied().collect() }
-    fn ul_history(&self) -> Vec<f64> { self.history_ul.iter().copied().collect() }
+    /// Fold one sample of the monitored interface in (also used by replay).
+    fn push(&mut self, stats: &BandwidthStats, ts: i64) {
+        // Sampled too soon after the previous call — nothing to record.
+        if stats.elapsed < 0.001 { return; }
+        self.replay_clock += stats.elapsed;
+        self.track.push(stats, self.history_size, ts);
+    }
+
+    /// Latest sample of the monitored interface.
+    fn current(&self) -> Option<&BandwidthStats> {
+        self.latest.iter().find(|(n, _)| n == &self.interface).map(|(_, s)| s)
+    }
+
+    /// Monitor `name` instead, keeping the history of the one we leave.
+    fn switch(&mut self, name: &str) {
+        if name == self.interface { return; }
+        let track = self.others.remove(name).unwrap_or_default();
+        let old = std::mem::replace(&mut self.track, track);
+        let old_name = std::mem::replace(&mut self.interface, name.to_string());
+        self.others.insert(old_name, old);
+    }
+
+    /// Start peak, average, session totals and runtime over; history stays.
+    fn reset_stats(&mut self) {
+        self.track.reset();
+        self.start_time = Instant::now();
+        self.replay_clock = 0.0;
+    }
+
+    /// Seconds covered so far: wall clock live, recorded time when replaying.
+    fn runtime(&self) -> f64 {
+        if self.sampler.is_some() { self.start_time.elapsed().as_secs_f64() } else { self.replay_clock }
+    }
+
+    /// Key in the history store: a followed device keeps its identifier
+    /// whatever it is called.
+    fn history_name(&self) -> String {
+        match self.device {
+            Some((ref dev, ref name)) if *name == self.interface => dev.to_string(),
+            _ => self.interface.clone(),
+        }
+    }
+
+    /// Whether the view is on a followed device that is missing.
+    fn device_gone(&self) -> bool {
+        self.gone && self.device.as_ref().is_some_and(|(_, n)| *n == self.interface)
+    }
+
+    /// The last sample of every real interface, without the group's sum.
+    fn devices(&self) -> &[(String, BandwidthStats)] {
+        let skip = self.group.is_some() as usize;
+        self.latest.get(skip..).unwrap_or_default()
+    }
+
+    /// Interfaces behind the current view: the group's members, or just the
+    /// monitored one.
+    fn members(&self) -> Vec<&str> {
+        match self.group {
+            Some(ref g) if g.name == self.interface => {
+                self.devices().iter().map(|(n, _)| n.as_str()).filter(|n| g.contains(n)).collect()
+            }
+            _ => vec![self.interface.as_str()],
+        }
+    }
+
+    /// Addresses of the monitored interface or group (none when replaying).
+    fn addresses(&self) -> Vec<std::net::IpAddr> {
+        let Some(ref sampler) = self.sampler else { return Vec::new() };
+        self.members().into_iter().flat_map(|n| sampler.addresses(n)).collect()
+    }
+
+    fn dl_history(&self) -> Vec<f64> { self.track.history_dl.iter().copied().collect() }
+    fn ul_history(&self) -> Vec<f64> { self.track.history_ul.iter().copied().collect() }
 }
 
 // ── Formatting ────────────────────────────────────────────────────────────────
@@ -295,43 +629,58 @@ fn fmt_total(b: u64) -> String {
     format!("{:.2} {}", v, U[i])
 }
 
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
 // ── Interface helpers ─────────────────────────────────────────────────────────
 
-fn list_interfaces() -> Result<()> {
-    let nets = Networks::new_with_refreshed_list();
-    println!("\n{}", styled("Available Network Interfaces:", C_CYAN, true));
-    println!("{}", "─".repeat(60));
-    for (name, data) in nets.iter() {
-        println!("  {} {}",
-            styled(name, C_WHITE, true),
-            styled(&format!("(RX: {} bytes, TX: {} bytes)",
-                data.total_received(), data.total_transmitted()), C_GREY, false));
+/// Without `-i`: the interface of the default route, else the busiest over
+/// `PROBE`, else the one with the most traffic so far — loopback only if
+/// nothing else is left. Returns the name and why it was chosen.
+fn select_best_interface(ns: Option<&netns::Namespace>, exclude: &[select::Pattern]) -> Result<(String, String)> {
+    let mut sampler = Sampler::open(ns)?;
+    let loopback = |n: &str| n.starts_with("lo") || n.starts_with("Loopback");
+    let route = route::defaults(ns).into_iter()
+        .find(|r| sampler.contains(&r.interface) && !select::excluded(&r.interface, exclude) && !loopback(&r.interface));
+    if let Some(r) = route {
+        let why = match r.gateway {
+            Some(gw) => format!("default route via {}", gw),
+            None     => "default route".to_string(),
+        };
+        return Ok((r.interface, why));
     }
-    println!();
-    Ok(())
-}
 
-fn select_best_interface() -> Result<String> {
-    Networks::new_with_refreshed_list()
-        .iter()
-        .max_by_key(|(_, d)| d.total_received() + d.total_transmitted())
-        .map(|(n, _)| n.clone())
+    std::thread::sleep(PROBE);
+    let mut samples: Vec<(String, BandwidthStats)> = sampler.sample().into_iter()
+        .filter(|(n, _)| !select::excluded(n, exclude))
+        .collect();
+    if samples.iter().any(|(n, _)| !loopback(n)) { samples.retain(|(n, _)| !loopback(n)); }
+    let rate = |s: &BandwidthStats| s.download_bps + s.upload_bps;
+    if let Some((n, _)) = samples.iter().filter(|(_, s)| rate(s) > 0.0).max_by(|a, b| rate(&a.1).total_cmp(&rate(&b.1))) {
+        return Ok((n.clone(), format!("busiest over {}ms, no default route", PROBE.as_millis())));
+    }
+    samples.into_iter()
+        .max_by_key(|(_, s)| s.total_rx + s.total_tx)
+        .map(|(n, _)| (n, "most traffic so far, no default route".to_string()))
         .context("No network interfaces found")
 }
 
-fn resolve_interface(pattern: &str) -> Result<String> {
-    let nets = Networks::new_with_refreshed_list();
-    let all: Vec<String> = nets.iter().map(|(n, _)| n.clone()).collect();
-    if all.iter().any(|n| n == pattern) { return Ok(pattern.to_string()); }
-    let low = pattern.to_lowercase();
-    let mut matches: Vec<String> = all.iter()
-        .filter(|n| n.to_lowercase().contains(&low))
-        .cloned().collect();
-    if matches.is_empty() {
-        anyhow::bail!("No interface matches '{}'. Available:\n{}", pattern, all.join("\n"));
-    }
-    matches.sort_by_key(|s| s.len());
-    Ok(matches.remove(0))
+fn resolve_interface(pattern: &str, ns: Option<&netns::Namespace>, exclude: &[select::Pattern]) -> Result<select::Target> {
+    let mut sampler = Sampler::open(ns)?;
+    let all: Vec<String> = sampler.totals().into_iter().map(|(n, _)| n).collect();
+    let device = match select::Device::parse(pattern) {
+        Some(d) => Some(d.map_err(anyhow::Error::msg)?),
+        // Altnames share the namespace of names, so an exact name never is one.
+        None if ns.is_none() && !all.iter().any(|n| n == pattern) => iface::canonical_name(pattern)
+            .filter(|n| n != pattern)
+            .map(|_| select::Device::Altname(pattern.to_string())),
+        None => None,
+    };
+    let Some(dev) = device else { return select::resolve(pattern, exclude, &all) };
+    if let Some(ns) = ns { anyhow::bail!("-i {} looks up this host's interfaces, not those of the {}", dev, ns); }
+    let name = sampler.find(&dev).with_context(|| format!("No interface has {}", dev.describe()))?;
+    Ok(select::Target::Device(dev, name))
 }
 
 // ── Safe plot width (BUG 2 + BUG 3 fix) ──────────────────────────────────────
@@ -357,32 +706,40 @@ fn safe_plot_width(user_requested: usize, data_len: usize, term_cols: usize) ->
 
     terminal_budget
         .min(data_len)       // never wider than available data points
-        .min(MAX_PLOT_WIDTH)
-        .max(MIN_PLOT_WIDTH)
+        .clamp(MIN_PLOT_WIDTH, MAX_PLOT_WIDTH)
 }
 
 // ── Chart renderer ────────────────────────────────────────────────────────────
 
 /// Print a single chart to stdout using the pingmon.rs streaming pattern:
 ///   label line → chart lines with clear_to_eol() after each.
-/// The very last chart line does NOT emit a newline; the caller decides.
-fn print_chart(data: &[f64], height: usize, plot_width: usize, col: u8, label: &str) {
-    if data.is_empty() || height == 0 || plot_width == 0 { return; }
-
-    // Take only the most recent plot_width samples.
-    let slice = {
-        let start = data.len().saturating_sub(plot_width);
-        &data[start..]
-    };
-    if slice.is_empty() { return; }
-
-    // KEY: pass slice.len() as width, NOT plot_width.
-    // Passing plot_width when slice.len() < plot_width causes leading whitespace.
-    let config = Config::default()
+/// `slice` is one value per column, already windowed (see `view::Window`).
+/// With a cursor, a marker line under the chart points at that column.
+/// The very last line does NOT emit a newline; the caller decides.
+/// Returns the lines printed after the label and the axis column, if any.
+fn print_chart(slice: &[f64], height: usize, col: u8, label: &str, cursor: Option<usize>,
+               max: Option<f64>) -> (usize, Option<usize>) {
+    if slice.is_empty() || height == 0 { return (0, None); }
+
+    // KEY: width follows slice.len(), NOT the terminal width.
+    // A canvas wider than the data causes leading whitespace.
+    // rasciichart keeps canvas column 0 for the axis, hence the +1 — without
+    // it the newest sample is never drawn.
+    let mut config = Config::default()
         .with_height(height)
-        .with_width(slice.len())
+        .with_width(slice.len() + 1)
         .with_labels(true)
         .with_label_format("{:.1}".to_string());
+    // A fixed scale (--y-max): values above it are drawn at the top.
+    let clamped: Vec<f64>;
+    let slice = match max {
+        Some(m) => {
+            config = config.with_min(0.0).with_max(m);
+            clamped = slice.iter().map(|v| v.min(m)).collect();
+            &clamped[..]
+        }
+        None => slice,
+    };
 
     // Label header line.
     print!("{}", styled(label, col, true));
@@ -393,15 +750,26 @@ fn print_chart(data: &[f64], height: usize, plot_width: usize, col: u8, label: &
         Err(e) => {
             print!("{}", styled(&format!("Chart error: {}", e), C_WHITE, false));
             clear_to_eol();
+            (1, None)
         }
         Ok(chart) => {
             let lines: Vec<&str> = chart.lines().collect();
             let last_idx = lines.len().saturating_sub(1);
             for (i, line) in lines.iter().enumerate() {
-                print!("\x1b[38;5;{}m{}\x1b[0m", col, line);
+                print!("{}", styled(line, col, false));
                 clear_to_eol();
                 if i < last_idx { println!(); }  // no newline on very last line
             }
+            // Value x sits in the column right after the axis plus x. A flat
+            // series comes back as a bare number with no axis.
+            let axis = lines.first().and_then(|l| l.chars().position(|c| c == '│'));
+            if let (Some(axis), Some(cursor)) = (axis, cursor) {
+                println!();
+                print!("\r{}{}", " ".repeat(axis + 1 + cursor), styled("▲", C_YELLOW, true));
+                clear_to_eol();
+                return (lines.len() + 1, Some(axis));
+            }
+            (lines.len(), axis)
         }
     }
 }
@@ -411,45 +779,73 @@ fn print_chart(data: &[f64], height: usize, plot_width: usize, col: u8, label: &
 /// Render one complete UI frame.
 /// Uses the pingmon.rs pattern: cursor_home → lines with clear_to_eol
 /// → clear_to_eos → single flush. No alternate screen, no String padding.
-fn render_frame(monitor: &NetworkMonitor, stats: &BandwidthStats, args: &Args) {
-    // BUG 1 fix: read width fresh every frame via term_size, not crossterm.
-    let tw = term_cols();
+/// `status` replaces the quit hint on the speeds line; `inspect` picks the
+/// window of history shown and the cursor column. Returns where the charts
+/// landed on screen, for the mouse.
+fn render_frame(monitor: &NetworkMonitor, stats: &BandwidthStats, args: &Args, status: Option<&str>,
+                inspect: view::Inspect) -> Vec<view::ChartArea> {
+    // BUG 1 fix: width is read fresh every frame via term_size, not crossterm.
+    let window = view::Window::new(monitor.track.history_dl.len(), args, inspect);
+    let mut areas = Vec::new();
+
+    let (c_dl, c_ul) = args.display.colors(&monitor.interface);
 
     cursor_home();
 
     // ── Header ──────────────────────────────────────────────────────────────
     print!("{}", styled(
-        &format!("═══ Bandwidth Monitor ({}) ═══", monitor.interface),
+        &format!("═══ Bandwidth Monitor ({}) ═══", args.display.label(&monitor.interface)),
         C_CYAN, true));
+    if monitor.group.as_ref().is_some_and(|g| g.name == monitor.interface) {
+        let members = monitor.members();
+        let list = members.join(" + ");
+        let list = if list.chars().count() <= 60 { list } else { format!("{} interfaces", members.len()) };
+        print!("  {}", styled(&list, C_GREY, false));
+    } else if monitor.device_gone() {
+        let dev = monitor.device.as_ref().map(|(d, _)| d.describe()).unwrap_or_default();
+        print!("  {}", styled(&format!("{} is gone — waiting for it to return", dev), C_YELLOW, false));
+    }
     clear_to_eol(); println!();
 
     // ── Current speeds ───────────────────────────────────────────────────────
-    print!("{} {}  │  {} {}  {}",
-        styled("Download:", C_CYAN,   true), styled(&fmt_bps(stats.download_bps), C_WHITE, false),
-        styled("Upload:",   C_YELLOW, true), styled(&fmt_bps(stats.upload_bps),   C_WHITE, false),
-        styled("'q'/Ctrl-C to quit", C_GREY, false));
+    let link = link::speed(monitor, args);
+    print!("{} {}{}  │  {} {}{}  {}",
+        styled("Download:", c_dl, true), styled(&fmt_bps(stats.download_bps), C_WHITE, false),
+        link::percent(stats.download_bps, link),
+        styled("Upload:",   c_ul, true), styled(&fmt_bps(stats.upload_bps),   C_WHITE, false),
+        link::percent(stats.upload_bps, link),
+        styled(status.unwrap_or("'q'/Ctrl-C to quit"), C_GREY, false));
     clear_to_eol(); println!();
 
+    // ── Utilisation gauges (optional) ────────────────────────────────────────
+    if args.gauge {
+        print!("{}", link::gauge_line(stats.download_bps, stats.upload_bps, link, term_cols()));
+        clear_to_eol(); println!();
+    }
+
     // ── Summary (optional) ───────────────────────────────────────────────────
     if args.summary {
         print!("{} {}  │  {} {}",
-            styled("Peak DL:", C_CYAN,   false), styled(&fmt_bps(monitor.peak_dl), C_WHITE, false),
-            styled("Peak UL:", C_YELLOW, false), styled(&fmt_bps(monitor.peak_ul), C_WHITE, false));
+            styled("Peak DL:", c_dl, false), styled(&fmt_bps(monitor.track.peak_dl), C_WHITE, false),
+            styled("Peak UL:", c_ul, false), styled(&fmt_bps(monitor.track.peak_ul), C_WHITE, false));
         clear_to_eol(); println!();
 
         print!("{} {}  │  {} {}",
-            styled("Avg DL:", C_CYAN,   false), styled(&fmt_bps(monitor.avg_dl), C_WHITE, false),
-            styled("Avg UL:", C_YELLOW, false), styled(&fmt_bps(monitor.avg_ul), C_WHITE, false));
+            styled("Avg DL:", c_dl, false), styled(&fmt_bps(monitor.track.avg_dl), C_WHITE, false),
+            styled("Avg UL:", c_ul, false), styled(&fmt_bps(monitor.track.avg_ul), C_WHITE, false));
         clear_to_eol(); println!();
 
         print!("{} {}  │  {} {}",
-            styled("Total RX:", C_CYAN,   false), styled(&fmt_total(stats.total_rx), C_WHITE, false),
-            styled("Total TX:", C_YELLOW, false), styled(&fmt_total(stats.total_tx), C_WHITE, false));
+            styled("Total RX:", c_dl, false), styled(&fmt_total(stats.total_rx), C_WHITE, false),
+            styled("Total TX:", c_ul, false), styled(&fmt_total(stats.total_tx), C_WHITE, false));
         clear_to_eol(); println!();
 
-        print!("{} {:.1}s",
+        print!("{} {:.1}s  │  {} {} {}",
             styled("Runtime:", C_GREEN, false),
-            monitor.start_time.elapsed().as_secs_f64());
+            monitor.runtime(),
+            styled("Session:", C_GREEN, false),
+            styled(&format!("↓ {}", fmt_total(monitor.track.session_rx)), c_dl, false),
+            styled(&format!("↑ {}", fmt_total(monitor.track.session_tx)), c_ul, false));
         clear_to_eol(); println!();
     }
 
@@ -457,23 +853,28 @@ fn render_frame(monitor: &NetworkMonitor, stats: &BandwidthStats, args: &Args) {
     clear_to_eol(); println!();
 
     let show_both = !args.download && !args.upload;
+    // Screen row of the next chart's label: header, speeds, gauges, summary, blank.
+    let mut row = if args.summary { 7 } else { 3 } + args.gauge as usize;
+    let max = args.y_max.and_then(|m| m.resolve(link));
+    let mut area = |row: usize, (lines, axis): (usize, Option<usize>)| {
+        if let Some(axis) = axis { areas.push(view::ChartArea { rows: row + 1..row + 1 + lines, axis }); }
+        row + 1 + lines + 1
+    };
 
     // ── Download chart ───────────────────────────────────────────────────────
     if args.download || show_both {
-        let dl = monitor.dl_history();
+        let dl = window.series(&monitor.track.history_dl);
         if !dl.is_empty() {
-            let pw = safe_plot_width(args.width, dl.len(), tw);
-            print_chart(&dl, args.height, pw, C_CYAN, "▼ Download Speed");
+            row = area(row, print_chart(&dl, args.height, c_dl, "▼ Download Speed", inspect.cursor, max));
             println!(); clear_to_eol(); println!();
         }
     }
 
     // ── Upload chart ─────────────────────────────────────────────────────────
     if args.upload || show_both {
-        let ul = monitor.ul_history();
+        let ul = window.series(&monitor.track.history_ul);
         if !ul.is_empty() {
-            let pw = safe_plot_width(args.width, ul.len(), tw);
-            print_chart(&ul, args.height, pw, C_YELLOW, "▲ Upload Speed");
+            area(row, print_chart(&ul, args.height, c_ul, "▲ Upload Speed", inspect.cursor, max));
             // No trailing println — clear_to_eos erases leftover screen below.
         }
     }
@@ -481,24 +882,101 @@ fn render_frame(monitor: &NetworkMonitor, stats: &BandwidthStats, args: &Args) {
     // BUG 4 fix: erase everything below the last drawn line, then flush once.
     clear_to_eos();
     flush();
+    areas
 }
 
 // ── Monitor loop ──────────────────────────────────────────────────────────────
 
-fn monitor_bandwidth(args: Args) -> Result<()> {
-    let interface = if let Some(ref iface) = args.iface {
-        resolve_interface(iface)?
-    } else {
-        select_best_interface()?
+/// Everything besides the screen that consumes each interval: the history
+/// store (`--persist`), the session file (`--record`) and the exporters.
+struct Sinks {
+    store:     Option<store::HistoryStore>,
+    session:   Option<session::SessionWriter>,
+    exporters: export::Exporters,
+}
+
+impl Sinks {
+    fn open(args: &Args, interface: &str) -> Result<Self> {
+        Ok(Self {
+            store:     if args.persist { Some(store::HistoryStore::open(&args.data_dir())?) } else { None },
+            session:   args.record.as_deref().map(|p| session::SessionWriter::create(p, interface)).transpose()?,
+            exporters: export::Exporters::start(&args.export, args.history)?,
+        })
+    }
+
+    fn consume(&mut self, monitor: &NetworkMonitor, stats: &BandwidthStats) -> Result<()> {
+        if let Some(ref mut st) = self.store {
+            st.append(&store::Record {
+                ts:        unix_now(),
+                interface: monitor.history_name(),
+                rx:        stats.rx_delta,
+                tx:        stats.tx_delta,
+                secs:      stats.elapsed,
+            })?;
+        }
+        if let Some(ref mut se) = self.session {
+            if let Some((ref from, ref to)) = monitor.renamed { se.rename(from, to); }
+            se.write(&monitor.latest)?;
+        }
+        self.exporters.publish(monitor.devices());
+        Ok(())
+    }
+}
+
+/// `-i` if given, otherwise the best guess — with the reason for it.
+fn pick_interface(args: &Args) -> Result<(select::Target, Option<String>)> {
+    let ns = args.namespace();
+    match args.iface {
+        Some(ref iface) => {
+            let target = resolve_interface(iface, ns.as_ref(), &args.exclude)?;
+            let why = match target {
+                select::Target::Device(ref dev, _) => Some(format!("found by {}", dev.describe())),
+                _ => None,
+            };
+            Ok((target, why))
+        }
+        None            => select_best_interface(ns.as_ref(), &args.exclude)
+            .map(|(name, why)| (select::Target::Interface(name), Some(why))),
+    }
+}
+
+fn monitor_bandwidth(mut args: Args) -> Result<()> {
+    // Redirected to a file or pipe: cursor control and raw mode make no
+    // sense there, so log plain lines instead.
+    if args.output == OutputFormat::Human && !stdout().is_terminal() {
+        args.output = OutputFormat::Text;
+    }
+    if args.once {
+        return measure::run(&args);
+    }
+    // An ambiguous plain -i is an error for pipes; here it opens the picker
+    // on the candidates instead.
+    let ((target, why), choose) = match pick_interface(&args) {
+        Err(e) if args.output == OutputFormat::Human => match e.downcast::<select::Ambiguous>() {
+            Ok(a)  => ((select::Target::Interface(a.matches[0].clone()), None), Some(a.pattern)),
+            Err(e) => return Err(e),
+        },
+        r => (r?, None),
     };
+    if args.output != OutputFormat::Human {
+        return stream_samples(&args, target);
+    }
+    if let (select::Target::Group(g), Some(_)) = (&target, args.capture) {
+        anyhow::bail!("--capture watches a single interface, not the group '{}'", g.name);
+    }
 
-    println!("Monitoring interface: {}\n", styled(&interface, C_CYAN, true));
+    let within = args.namespace().map(|ns| format!(" ({})", ns)).unwrap_or_default();
+    let why = why.map(|w| format!(" — {}", styled(&w, C_GREY, false))).unwrap_or_default();
+    println!("Monitoring interface: {}{}{}\n", styled(&args.display.label(target.name()), C_CYAN, true), within, why);
 
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
     ctrlc::set_handler(move || { r.store(false, Ordering::SeqCst); })?;
 
-    let mut monitor = NetworkMonitor::new(interface, args.history)?;
+    let mut sinks = Sinks::open(&args, target.name())?;
+    let mut monitor = NetworkMonitor::new(target, args.history, args.namespace().as_ref())?;
+    let mut procs = args.processes.map(|_| procs::Processes::new()).transpose()?;
+    let mut capture = args.capture.map(|_| capture::Capture::start(&monitor.interface, args.filter.clone())).transpose()?;
 
     // Warm-up: discard first sample — elapsed includes init time → fake spike.
     let _ = monitor.update();
@@ -510,32 +988,71 @@ fn monitor_bandwidth(args: Args) -> Result<()> {
 
     // Raw mode only for keyboard reading.
     enable_raw_mode()?;
+    if !args.no_mouse { execute!(stdout(), EnableMouseCapture)?; }
+
+    let mut view = view::View::new();
+    if let Some(pattern) = choose { view.open_chooser(pattern); }
 
     let result: Result<()> = (|| {
         loop {
             if !running.load(Ordering::SeqCst) { break; }
 
+            let mut redraw = false;
+
             // Non-blocking poll — 50 ms keeps the UI snappy without busy-spin.
             if event::poll(Duration::from_millis(50))? {
-                if let Event::Key(k) = event::read()? {
-                    match k.code {
-                        KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => break,
-                        KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => break,
-                        _ => {}
-                    }
+                let action = match event::read()? {
+                    Event::Key(k)   => view.handle_key(k, &mut args, &mut monitor),
+                    Event::Mouse(m) => view.handle_mouse(m, &args, &mut monitor),
+                    _               => view::Action::Ignore,
+                };
+                match action {
+                    view::Action::Quit   => break,
+                    view::Action::Redraw => redraw = true,
+                    view::Action::Ignore => {}
                 }
             }
 
-            if last_update.elapsed() >= INTERVAL {
+            // `t` toggles the process table; start from a fresh baseline each time.
+            match (args.processes, &procs) {
+                (None, Some(_))    => procs = None,
+                (Some(_), None)    => match procs::Processes::new() {
+                    Ok(p)  => procs = Some(p),
+                    Err(_) => args.processes = None,
+                },
+                _ => {}
+            }
+
+            if last_update.elapsed() >= view.interval {
                 let stats = monitor.update()?;
-                render_frame(&monitor, &stats, &args);
+                sinks.consume(&monitor, &stats)?;
+                if let Some(ref mut p) = procs { p.sample(&monitor.addresses()); }
+                if let Some(ref mut c) = capture {
+                    // Follow the picker to another interface.
+                    if c.interface != monitor.interface {
+                        if let Ok(n) = capture::Capture::start(&monitor.interface, args.filter.clone()) { *c = n; }
+                    }
+                    c.sample();
+                }
                 last_update = Instant::now();
+                view.on_sample(&args, &monitor);
+                redraw |= !view.paused;
+            }
+
+            if redraw {
+                if let Some(stats) = monitor.current() {
+                    view.charts = render_frame(&monitor, stats, &args, Some(&view.status(&args, &monitor)), view.inspect);
+                    if let (Some(p), Some(n)) = (&procs, args.processes) { p.draw(n); }
+                    if let (Some(c), Some(n)) = (&capture, args.capture) { c.draw(n, args.group); }
+                    view.draw_overlay(&monitor);
+                }
             }
         }
         Ok(())
     })();
 
     // Restore terminal — no ? so it always runs, even after an error.
+    if !args.no_mouse { let _ = execute!(stdout(), DisableMouseCapture); }
     let _ = disable_raw_mode();
 
     // Print exit message below the last render.
@@ -548,11 +1065,63 @@ fn monitor_bandwidth(args: Args) -> Result<()> {
     Ok(())
 }
 
+// ── Streaming output ──────────────────────────────────────────────────────────
+
+/// `--output csv|jsonl|influx|text`: no raw mode, no screen control — one flushed line per
+/// interval on stdout so pipes and `tail -f` see every sample immediately.
+/// A closed pipe (e.g. `| head`) ends the stream quietly.
+fn stream_samples(args: &Args, target: select::Target) -> Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || { r.store(false, Ordering::SeqCst); })?;
+
+    let mut sinks = Sinks::open(args, target.name())?;
+    let mut monitor = NetworkMonitor::new(target, args.history, args.namespace().as_ref())?;
+    let _ = monitor.update();  // warm-up, same as the TUI
+    let mut last_update = Instant::now();
+
+    let Some(fmt) = output::LineFormat::new(args.output, args.delimiter, &args.export) else { return Ok(()) };
+    let mut out = stdout().lock();
+
+    let result: std::io::Result<()> = (|| {
+        if let Some(h) = fmt.header() {
+            writeln!(out, "{}", h)?;
+            out.flush()?;
+        }
+        while running.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(50));
+            if last_update.elapsed() < INTERVAL { continue; }
+            let stats = monitor.update().map_err(std::io::Error::other)?;
+            last_update = Instant::now();
+            sinks.consume(&monitor, &stats).map_err(std::io::Error::other)?;
+            let rec = output::SampleRecord::new(&chrono::Local::now(), &monitor, &stats);
+            writeln!(out, "{}", fmt.line(&rec))?;
+            out.flush()?;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        r => Ok(r?),
+    }
+}
+
 // ── Entry point ───────────────────────────────────────────────────────────────
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    config::apply(&mut args, &matches)?;
+    args.color.apply();
+    if args.print_config { return config::print_effective(&args); }
     if args.version { println!("{}", ColoredVersion); return Ok(()); }
-    if args.list    { return list_interfaces(); }
-    monitor_bandwidth(args)
+    if args.list    { return iface::list(&args); }
+    match args.command {
+        Some(Command::Report(ref r)) => report::run(r, &args.data_dir(), &args.exclude),
+        Some(Command::Record(ref r)) => daemon::run(r, &args.data_dir(), &args.export),
+        Some(Command::Replay(ref r)) => session::replay(r, &args),
+        Some(Command::Pcap(ref p))   => pcap::run(p, &args),
+        None                         => monitor_bandwidth(args),
+    }
 }
\ No newline at end of file
@@ -3,6 +3,9 @@
 //! Author: Hadi Cahyadi <cumulus13@gmail.com>
 //! License: MIT
 
+mod color;
+mod textlog;
+
 use crossterm::queue;
 use anyhow::{Context, Result};
 use clap::Parser;
@@ -18,12 +21,13 @@ use crossterm::{
     },
 };
 use std::collections::VecDeque;
-use std::io::{stdout, Write};
+use std::io::{stdout, IsTerminal, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sysinfo::Networks;
 use std::fmt;
+use color::ColorMode;
 
 const INTERVAL: Duration = Duration::from_secs(1);
 const DEFAULT_HISTORY: usize = 120;
@@ -88,6 +92,10 @@ struct Args {
     #[arg(long, default_value_t = DEFAULT_HISTORY)]
     history: usize,
 
+    /// Colourise output: auto (terminal and no NO_COLOR), always or never
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto, value_name = "WHEN")]
+    color: ColorMode,
+
     #[arg(short = 'v', short = 'V', long = "version", action = ArgAction::SetTrue)]
     version: bool,
 }
@@ -255,6 +263,7 @@ fn select_best_interface() -> Result<String> {
         .context("No network interfaces found")
 }
 
+#[allow(clippy::iter_kv_map)]
 fn resolve_interface(pattern: &str) -> Result<String> {
     let networks = Networks::new_with_refreshed_list();
     let interfaces: Vec<String> = networks.iter().map(|(name, _)| name.clone()).collect();
@@ -318,7 +327,9 @@ fn format_total_bytes(bytes: u64) -> String {
 }
 
 fn style_text(text: &str, color: Color, bold: bool) -> String {
-    if bold {
+    if !color::enabled() {
+        text.to_string()
+    } else if bold {
         format!("\x1b[1m\x1b[38;5;{}m{}\x1b[0m", color_to_256(color), text)
     } else {
         format!("\x1b[38;5;{}m{}\x1b[0m", color_to_256(color), text)
@@ -338,6 +349,7 @@ fn color_to_256(color: Color) -> u8 {
 }
 
 /// FIX: Improved graph rendering with proper alignment and smooth gradients
+#[allow(clippy::needless_range_loop)]
 fn render_chart(data: &[f64], height: usize, width: usize, color: Color) -> String {
     if data.is_empty() || height == 0 || width == 0 {
         return String::new();
@@ -423,7 +435,6 @@ fn render_chart(data: &[f64], height: usize, width: usize, color: Color) -> Stri
     let label_min = format_label(min_val);
     let label_mid = format_label((max_val + min_val) / 2.0);
 
-    let color_code = color_to_256(color);
     let mut output = String::new();
 
     // FIX: Use ASCII pipe character for perfect vertical alignment
@@ -441,8 +452,9 @@ fn render_chart(data: &[f64], height: usize, width: usize, color: Color) -> Stri
         let line: String = row.iter().collect();
         // FIX: Use simple ASCII '|' for vertical line - always aligned
         output.push_str(&format!(
-            "{} \x1b[38;5;{}m|{}\x1b[0m\n",
-            label, color_code, line
+            "{} {}\n",
+            label,
+            style_text(&format!("|{}", line), color, false)
         ));
     }
 
@@ -555,6 +567,21 @@ fn monitor_bandwidth(args: Args) -> Result<()> {
         r.store(false, Ordering::SeqCst);
     })?;
 
+    // Redirected to a file or pipe: plain lines instead of the alternate screen.
+    if !stdout().is_terminal() {
+        return textlog::run(INTERVAL, &running, || {
+            let (prev_rx, prev_tx) = (monitor.prev_rx, monitor.prev_tx);
+            let stats = monitor.update()?;
+            Ok(textlog::Line {
+                interface: monitor.interface.clone(),
+                rx_bps: stats.download_bps,
+                tx_bps: stats.upload_bps,
+                rx_bytes: stats.total_rx.saturating_sub(prev_rx),
+                tx_bytes: stats.total_tx.saturating_sub(prev_tx),
+            })
+        });
+    }
+
     let mut stdout = stdout();
     execute!(stdout, EnterAlternateScreen, Hide)?;
     enable_raw_mode()?;
@@ -620,6 +647,7 @@ fn monitor_bandwidth(args: Args) -> Result<()> {
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    args.color.apply();
 
     if args.version {
         println!("{}", ColoredVersion::new());
@@ -2,6 +2,9 @@
 //! Author: Hadi Cahyadi <cumulus13@gmail.com>
 //! License: MIT
 
+mod color;
+mod textlog;
+
 use anyhow::{Context, Result};
 use clap::Parser;
 use clap::ArgAction;
@@ -17,12 +20,13 @@ use crossterm::{
 };
 use rasciichart::{plot_with_config, Config};
 use std::collections::VecDeque;
-use std::io::{stdout, Write};
+use std::io::{stdout, IsTerminal, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use sysinfo::Networks;
 use std::fmt;
+use color::ColorMode;
 
 const INTERVAL: Duration = Duration::from_secs(1);
 const DEFAULT_HISTORY: usize = 120;
@@ -90,6 +94,10 @@ struct Args {
     #[arg(long, default_value_t = DEFAULT_HISTORY)]
     history: usize,
 
+    /// Colourise output: auto (terminal and no NO_COLOR), always or never
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto, value_name = "WHEN")]
+    color: ColorMode,
+
     #[arg(short = 'v', short = 'V', long = "version", action = ArgAction::SetTrue)]
     version: bool,
 }
@@ -274,6 +282,7 @@ fn select_best_interface() -> Result<String> {
         .context("No suitable network interfaces found")
 }
 
+#[allow(clippy::iter_kv_map)]
 fn resolve_interface(pattern: &str) -> Result<String> {
     let networks = Networks::new_with_refreshed_list();
     let interfaces: Vec<String> = networks.iter().map(|(name, _)| name.clone()).collect();
@@ -337,7 +346,9 @@ fn format_total_bytes(bytes: u64) -> String {
 }
 
 fn style_text(text: &str, color: Color, bold: bool) -> String {
-    if bold {
+    if !color::enabled() {
+        text.to_string()
+    } else if bold {
         format!("\x1b[1m\x1b[38;5;{}m{}\x1b[0m", color_to_256(color), text)
     } else {
         format!("\x1b[38;5;{}m{}\x1b[0m", color_to_256(color), text)
@@ -391,10 +402,9 @@ fn render_chart_rasciichart(
     };
 
     // Add color to the chart
-    let color_code = color_to_256(color);
     let colored_chart: String = chart
         .lines()
-        .map(|line| format!("\x1b[38;5;{}m{}\x1b[0m", color_code, line))
+        .map(|line| style_text(line, color, false))
         .collect::<Vec<_>>()
         .join("\n");
 
@@ -531,6 +541,22 @@ fn monitor_bandwidth(args: Args) -> Result<()> {
         r.store(false, Ordering::SeqCst);
     })?;
 
+    // Redirected to a file or pipe: plain lines instead of the alternate screen.
+    if !stdout().is_terminal() {
+        let mut monitor = monitor.lock().unwrap();
+        return textlog::run(INTERVAL, &running, || {
+            let (prev_rx, prev_tx) = (monitor.prev_rx, monitor.prev_tx);
+            let stats = monitor.update()?;
+            Ok(textlog::Line {
+                interface: monitor.interface.clone(),
+                rx_bps: stats.download_bps,
+                tx_bps: stats.upload_bps,
+                rx_bytes: stats.total_rx.saturating_sub(prev_rx),
+                tx_bytes: stats.total_tx.saturating_sub(prev_tx),
+            })
+        });
+    }
+
     let mut stdout = stdout();
     execute!(stdout, EnterAlternateScreen, Hide)?;
     enable_raw_mode()?;
@@ -608,6 +634,7 @@ fn monitor_bandwidth(args: Args) -> Result<()> {
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    args.color.apply();
 
     if args.version {
         println!("{}", ColoredVersion::new());
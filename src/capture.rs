@@ -0,0 +1,380 @@
+// File: src/capture.rs
+//! `--capture [N]`: iftop-style top talkers on the monitored interface,
+//! beneath the charts.
+//!
+//!   sudo bandwidthmon -i eth0 --capture
+//!   sudo bandwidthmon -i eth0 --capture 20 --filter "port 443 or port 80"
+//!   sudo bandwidthmon -i lo --capture --filter "tcp and port 5201"
+//!
+//! An AF_PACKET socket bound to the interface sees every packet in both
+//! directions, outgoing ones flagged by the kernel. A reader thread parses
+//! the IP header, applies `--filter` and adds the packet's length to its
+//! flow; each interval the loop turns those bytes into rates. Flows are
+//! keyed by protocol and both endpoints, seen from this host: outgoing
+//! packets are upload, everything else download. `g` (or `--group host`)
+//! sums flows per remote host instead.
+//!
+//! Needs root or CAP_NET_RAW, and Linux.
+
+use crate::filter::Filter;
+use crate::packet::{self, Packet};
+use crate::{clear_to_eol, fmt_bps, fmt_total, styled, C_CYAN, C_GREY, C_WHITE};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Flows idle this long drop out of the table.
+const IDLE: Duration = Duration::from_secs(60);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Group { Flow, Host }
+
+/// Protocol plus (address, port) of both ends, local first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct FlowKey {
+    proto:  u8,
+    local:  (IpAddr, u16),
+    remote: (IpAddr, u16),
+}
+
+impl FlowKey {
+    fn of(p: &Packet, outgoing: bool) -> Self {
+        let (src, dst) = ((p.src, p.sport), (p.dst, p.dport));
+        if outgoing {
+            Self { proto: p.proto, local: src, remote: dst }
+        } else {
+            Self { proto: p.proto, local: dst, remote: src }
+        }
+    }
+}
+
+struct Talker {
+    rx_bps:   f64,
+    tx_bps:   f64,
+    rx_total: u64,
+    tx_total: u64,
+    last:     Instant,   // last interval with traffic
+}
+
+/// A table row: a flow or a remote host.
+struct Row {
+    label:  String,
+    flows:  usize,
+    rx_bps: f64,
+    tx_bps: f64,
+    total:  u64,
+}
+
+type Pending = Arc<Mutex<HashMap<FlowKey, (u64, u64)>>>;
+
+pub struct Capture {
+    pub interface: String,
+    filter:        Option<Filter>,
+    pending:       Pending,                    // bytes since the last sample, from the reader
+    stop:          Arc<AtomicBool>,
+    reader:        Option<JoinHandle<()>>,
+    flows:         HashMap<FlowKey, Talker>,
+    prev_time:     Instant,
+}
+
+impl Capture {
+    pub fn start(interface: &str, filter: Option<Filter>) -> Result<Self> {
+        let sock = sys::open(interface)?;
+        let pending: Pending = Arc::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (pending, stop, filter) = (pending.clone(), stop.clone(), filter.clone());
+            std::thread::spawn(move || read_loop(sock, filter, pending, stop))
+        };
+        Ok(Self {
+            interface: interface.to_string(),
+            filter,
+            pending,
+            stop,
+            reader: Some(reader),
+            flows: HashMap::new(),
+            prev_time: Instant::now(),
+        })
+    }
+
+    /// Turn the bytes captured since the last call into per-flow rates.
+    pub fn sample(&mut self) {
+        let now = Instant::now();
+        let secs = now.duration_since(self.prev_time).as_secs_f64().max(0.001);
+        let bytes = std::mem::take(&mut *self.pending.lock().unwrap());
+        for t in self.flows.values_mut() { t.rx_bps = 0.0; t.tx_bps = 0.0; }
+        for (key, (rx, tx)) in bytes {
+            let t = self.flows.entry(key).or_insert(Talker { rx_bps: 0.0, tx_bps: 0.0, rx_total: 0, tx_total: 0, last: now });
+            t.rx_bps = rx as f64 / secs;
+            t.tx_bps = tx as f64 / secs;
+            t.rx_total += rx;
+            t.tx_total += tx;
+            t.last = now;
+        }
+        self.flows.retain(|_, t| now.duration_since(t.last) < IDLE);
+        self.prev_time = now;
+    }
+
+    fn rows(&self, group: Group) -> Vec<Row> {
+        let mut rows: HashMap<String, Row> = HashMap::new();
+        for (k, t) in &self.flows {
+            let label = match group {
+                Group::Flow => format!("{:<5} {:<23} {:<23}", packet::proto_name(k.proto), endpoint(k.local), endpoint(k.remote)),
+                Group::Host => k.remote.0.to_string(),
+            };
+            let r = rows.entry(label.clone()).or_insert(Row { label, flows: 0, rx_bps: 0.0, tx_bps: 0.0, total: 0 });
+            r.flows  += 1;
+            r.rx_bps += t.rx_bps;
+            r.tx_bps += t.tx_bps;
+            r.total  += t.rx_total + t.tx_total;
+        }
+        let mut rows: Vec<Row> = rows.into_values().collect();
+        rows.sort_by(|a, b| (b.rx_bps + b.tx_bps).total_cmp(&(a.rx_bps + a.tx_bps)).then(b.total.cmp(&a.total)));
+        rows
+    }
+
+    /// The top-`n` table, continuing below whatever was drawn last.
+    pub fn draw(&self, n: usize, group: Group) {
+        let (what, other) = match group { Group::Flow => ("flows", "hosts"), Group::Host => ("remote hosts", "flows") };
+        let filter = self.filter.as_ref().map(|f| format!(", filter: {}", f)).unwrap_or_default();
+        print!("\r\n");
+        clear_to_eol();
+        print!("\r\n{} {}",
+            styled(&format!("Top {} on {}{}", what, self.interface, filter), C_CYAN, true),
+            styled(&format!("— 'g' {}", other), C_GREY, false));
+        clear_to_eol();
+        let head = match group {
+            Group::Flow => format!("{:<5} {:<23} {:<23}", "PROTO", "LOCAL", "REMOTE"),
+            Group::Host => format!("{:<39} {:>5}", "REMOTE HOST", "FLOWS"),
+        };
+        print!("\r\n{}", styled(&format!("{}  {:>14}  {:>14}  {:>10}", head, "↓ Download", "↑ Upload", "Total"), C_CYAN, false));
+        clear_to_eol();
+        let rows = self.rows(group);
+        if rows.is_empty() {
+            print!("\r\n{}", styled("  (no packets captured yet)", C_GREY, false));
+            clear_to_eol();
+        }
+        for r in rows.iter().take(n) {
+            let label = match group {
+                Group::Flow => r.label.clone(),
+                Group::Host => format!("{:<39} {:>5}", r.label, r.flows),
+            };
+            let idle = r.rx_bps + r.tx_bps == 0.0;
+            print!("\r\n{}", styled(&format!("{}  {:>14}  {:>14}  {:>10}",
+                label, fmt_bps(r.rx_bps), fmt_bps(r.tx_bps), fmt_total(r.total)),
+                if idle { C_GREY } else { C_WHITE }, false));
+            clear_to_eol();
+        }
+        crate::clear_to_eos();
+        crate::flush();
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(r) = self.reader.take() { let _ = r.join(); }
+    }
+}
+
+/// `10.0.0.1:443`, `[::1]:443`, or just the address when there is no port.
+fn endpoint((addr, port): (IpAddr, u16)) -> String {
+    match (addr, port) {
+        (a, 0)              => a.to_string(),
+        (IpAddr::V6(a), p)  => format!("[{}]:{}", a, p),
+        (a, p)              => format!("{}:{}", a, p),
+    }
+}
+
+fn read_loop(sock: sys::Socket, filter: Option<Filter>, pending: Pending, stop: Arc<AtomicBool>) {
+    let mut buf = vec![0u8; 65536];
+    while !stop.load(Ordering::SeqCst) {
+        // Times out every 200 ms so `stop` is noticed.
+        let Some((len, outgoing)) = sock.recv(&mut buf) else { continue };
+        account(&mut pending.lock().unwrap(), &buf[..len.min(buf.len())], len, outgoing, filter.as_ref());
+    }
+}
+
+/// Add one captured frame of `len` bytes to its flow, if it is IP and passes
+/// the filter: to upload when this host sent it, download otherwise.
+fn account(flows: &mut HashMap<FlowKey, (u64, u64)>, frame: &[u8], len: usize, outgoing: bool, filter: Option<&Filter>) {
+    let Some(p) = packet::parse_ip(frame, len) else { return };
+    if filter.is_some_and(|f| !f.matches(&p)) { return; }
+    let e = flows.entry(FlowKey::of(&p, outgoing)).or_default();
+    if outgoing { e.1 += p.len as u64; } else { e.0 += p.len as u64; }
+}
+
+// ── Linux ─────────────────────────────────────────────────────────────────────
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use anyhow::{bail, Context, Result};
+    use std::ffi::CString;
+
+    /// `sll_pkttype` of packets this host sent.
+    const PACKET_OUTGOING: u8 = 4;
+
+    /// An AF_PACKET datagram socket: packets arrive without link headers.
+    pub struct Socket(i32);
+
+    pub fn open(interface: &str) -> Result<Socket> {
+        let name = CString::new(interface).context("Invalid interface name")?;
+        let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if ifindex == 0 { bail!("Interface '{}' not found", interface); }
+
+        let proto = (libc::ETH_P_ALL as u16).to_be();
+        let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, proto as i32) };
+        if fd < 0 {
+            let e = std::io::Error::last_os_error();
+            if e.raw_os_error() == Some(libc::EPERM) {
+                bail!("Packet capture needs root or CAP_NET_RAW (e.g. sudo setcap cap_net_raw+ep $(which bandwidthmon))");
+            }
+            bail!("Cannot open capture socket: {}", e);
+        }
+        let sock = Socket(fd);
+
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as u16;
+        addr.sll_protocol = proto;
+        addr.sll_ifindex = ifindex as i32;
+        let rc = unsafe {
+            libc::bind(fd, (&addr as *const libc::sockaddr_ll).cast(), std::mem::size_of::<libc::sockaddr_ll>() as u32)
+        };
+        if rc < 0 { bail!("Cannot bind capture socket to {}: {}", interface, std::io::Error::last_os_error()); }
+
+        let timeout = libc::timeval { tv_sec: 0, tv_usec: 200_000 };
+        unsafe {
+            libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_RCVTIMEO, (&timeout as *const libc::timeval).cast(),
+                std::mem::size_of::<libc::timeval>() as u32);
+        }
+        Ok(sock)
+    }
+
+    impl Socket {
+        /// One packet: its full length (even if `buf` was too small) and
+        /// whether this host sent it. None on timeout.
+        pub fn recv(&self, buf: &mut [u8]) -> Option<(usize, bool)> {
+            let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+            let mut alen = std::mem::size_of::<libc::sockaddr_ll>() as u32;
+            let n = unsafe {
+                libc::recvfrom(self.0, buf.as_mut_ptr().cast(), buf.len(), libc::MSG_TRUNC,
+                    (&mut addr as *mut libc::sockaddr_ll).cast(), &mut alen)
+            };
+            (n > 0).then_some((n as usize, addr.sll_pkttype == PACKET_OUTGOING))
+        }
+    }
+
+    impl Drop for Socket {
+        fn drop(&mut self) { unsafe { libc::close(self.0); } }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use anyhow::{bail, Result};
+
+    pub struct Socket;
+
+    pub fn open(_interface: &str) -> Result<Socket> {
+        bail!("Packet capture is only available on Linux")
+    }
+
+    impl Socket {
+        pub fn recv(&self, _buf: &mut [u8]) -> Option<(usize, bool)> { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::PROTO_UDP;
+    use std::net::UdpSocket;
+
+    /// An IPv4 UDP packet from `src:sport` to `dst:dport`, as AF_PACKET
+    /// hands it over (no link header).
+    fn udp(src: [u8; 4], sport: u16, dst: [u8; 4], dport: u16) -> Vec<u8> {
+        let mut f = vec![0x45, 0, 0, 28, 0, 0, 0, 0, 64, PROTO_UDP, 0, 0];
+        f.extend(src);
+        f.extend(dst);
+        f.extend(sport.to_be_bytes());
+        f.extend(dport.to_be_bytes());
+        f.extend([0, 8, 0, 0]);
+        f
+    }
+
+    fn idle_capture(filter: Option<Filter>) -> Capture {
+        Capture {
+            interface: "test0".to_string(),
+            filter,
+            pending:   Arc::default(),
+            stop:      Arc::new(AtomicBool::new(false)),
+            reader:    None,
+            flows:     HashMap::new(),
+            prev_time: Instant::now() - Duration::from_secs(2),
+        }
+    }
+
+    #[test]
+    fn frames_are_accounted_to_flows() {
+        let (me, peer, other) = ([10, 0, 0, 1], [192, 168, 1, 2], [192, 168, 1, 3]);
+        let filter = Filter::parse("not port 53").unwrap();
+        let mut cap = idle_capture(Some(filter));
+        {
+            let mut pending = cap.pending.lock().unwrap();
+            let f = cap.filter.as_ref();
+            // Both directions of one conversation land in one flow.
+            account(&mut pending, &udp(me, 40000, peer, 443), 1000, true, f);
+            account(&mut pending, &udp(peer, 443, me, 40000), 3000, false, f);
+            account(&mut pending, &udp(peer, 443, me, 40000), 1000, false, f);
+            account(&mut pending, &udp(me, 40001, peer, 443), 500, true, f);
+            account(&mut pending, &udp(other, 443, me, 40002), 100, false, f);
+            // Filtered out, and not IP at all.
+            account(&mut pending, &udp(me, 40003, peer, 53), 9999, true, f);
+            account(&mut pending, &[0x00; 40], 9999, false, f);
+        }
+        cap.sample();
+
+        let local: IpAddr = me.into();
+        let flow = FlowKey { proto: PROTO_UDP, local: (local, 40000), remote: (IpAddr::from(peer), 443) };
+        let t = &cap.flows[&flow];
+        assert_eq!((t.rx_total, t.tx_total), (4000, 1000));
+        assert!((t.rx_bps - 2000.0).abs() < 50.0 && (t.tx_bps - 500.0).abs() < 50.0, "{} {}", t.rx_bps, t.tx_bps);
+        assert_eq!(cap.flows.len(), 3);
+
+        let hosts = cap.rows(Group::Host);
+        let summary: Vec<_> = hosts.iter().map(|r| (r.label.as_str(), r.flows, r.total)).collect();
+        assert_eq!(summary, [("192.168.1.2", 2, 5500), ("192.168.1.3", 1, 100)]);
+
+        // A quiet interval keeps the flows, at zero rate.
+        cap.sample();
+        assert!(cap.flows.values().all(|t| t.rx_bps == 0.0 && t.tx_bps == 0.0));
+        assert_eq!(cap.rows(Group::Flow).len(), 3);
+    }
+
+    #[test]
+    #[ignore = "needs root (CAP_NET_RAW)"]
+    fn udp_on_loopback_shows_up_as_a_flow() {
+        let rx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (from, to) = (tx.local_addr().unwrap().port(), rx.local_addr().unwrap().port());
+        let filter = Filter::parse(&format!("udp and port {}", to)).unwrap();
+        let mut cap = Capture::start("lo", Some(filter)).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        for _ in 0..10 { tx.send_to(&[0; 1000], ("127.0.0.1", to)).unwrap(); }
+        std::thread::sleep(Duration::from_millis(300));
+        cap.sample();
+
+        let lo: IpAddr = "127.0.0.1".parse().unwrap();
+        let flow = FlowKey { proto: PROTO_UDP, local: (lo, from), remote: (lo, to) };
+        let t = cap.flows.get(&flow).expect("flow not captured");
+        assert!(t.tx_total >= 10 * 1000, "only {} bytes sent", t.tx_total);
+        assert!(cap.flows.keys().all(|k| k.proto == PROTO_UDP && (k.local.1 == to || k.remote.1 == to)));
+    }
+}
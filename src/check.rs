@@ -0,0 +1,267 @@
+// File: src/check.rs
+//! Threshold checks on a `--once` summary: `--warn-if` / `--fail-if` and the
+//! Nagios / Icinga plugin output of `--nagios`.
+//!
+//!   bandwidthmon -i eth0 --once --fail-if "avg_rx > 100MB/s" --fail-if "peak_tx < 1KB/s"
+//!   bandwidthmon -i eth0 --once --nagios --warn-if "avg_rx > 50MB/s" --fail-if "avg_rx > 90MB/s"
+//!
+//! A condition describes the *bad* state; when it holds the check fails.
+//! Exit codes follow the plugin convention in every output mode:
+//!   0  OK   1  WARNING   2  CRITICAL   3  UNKNOWN (measurement failed)
+
+use crate::measure::Summary;
+use crate::{fmt_bps, fmt_total};
+use std::fmt;
+
+pub const EXIT_OK:       i32 = 0;
+pub const EXIT_WARNING:  i32 = 1;
+pub const EXIT_CRITICAL: i32 = 2;
+pub const EXIT_UNKNOWN:  i32 = 3;
+
+// ── Conditions ────────────────────────────────────────────────────────────────
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Metric { AvgRx, AvgTx, PeakRx, PeakTx, P95Rx, P95Tx, RxBytes, TxBytes }
+
+const METRICS: &[(&str, Metric)] = &[
+    ("avg_rx",   Metric::AvgRx),   ("avg_tx",   Metric::AvgTx),
+    ("peak_rx",  Metric::PeakRx),  ("peak_tx",  Metric::PeakTx),
+    ("p95_rx",   Metric::P95Rx),   ("p95_tx",   Metric::P95Tx),
+    ("rx_bytes", Metric::RxBytes), ("tx_bytes", Metric::TxBytes),
+];
+
+impl Metric {
+    fn name(self) -> &'static str {
+        METRICS.iter().find(|(_, m)| *m == self).map_or("?", |(n, _)| n)
+    }
+
+    fn of(self, s: &Summary) -> f64 {
+        match self {
+            Metric::AvgRx   => s.avg_rx_bps,
+            Metric::AvgTx   => s.avg_tx_bps,
+            Metric::PeakRx  => s.peak_rx_bps,
+            Metric::PeakTx  => s.peak_tx_bps,
+            Metric::P95Rx   => s.p95_rx_bps,
+            Metric::P95Tx   => s.p95_tx_bps,
+            Metric::RxBytes => s.rx_bytes as f64,
+            Metric::TxBytes => s.tx_bytes as f64,
+        }
+    }
+
+    fn is_bytes(self) -> bool { matches!(self, Metric::RxBytes | Metric::TxBytes) }
+
+    fn fmt_value(self, v: f64) -> String {
+        if self.is_bytes() { fmt_total(v as u64) } else { fmt_bps(v).trim_start().to_string() }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Op { Gt, Ge, Lt, Le }
+
+impl Op {
+    fn holds(self, a: f64, b: f64) -> bool {
+        match self { Op::Gt => a > b, Op::Ge => a >= b, Op::Lt => a < b, Op::Le => a <= b }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self { Op::Gt => ">", Op::Ge => ">=", Op::Lt => "<", Op::Le => "<=" }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Condition {
+    metric: Metric,
+    op:     Op,
+    value:  f64,
+}
+
+impl Condition {
+    /// `"<metric> <op> <value>"`, e.g. `avg_rx > 100MB/s`, `rx_bytes>=2GB`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let i = s.find(['<', '>']).ok_or_else(|| format!("'{}': expected <metric> <op> <value>", s))?;
+        let (lhs, rest) = s.split_at(i);
+        let (op, rhs) = match rest.as_bytes() {
+            [b'>', b'=', ..] => (Op::Ge, &rest[2..]),
+            [b'<', b'=', ..] => (Op::Le, &rest[2..]),
+            [b'>', ..]       => (Op::Gt, &rest[1..]),
+            _                => (Op::Lt, &rest[1..]),
+        };
+        let lhs = lhs.trim().to_lowercase();
+        let metric = METRICS.iter()
+            .find(|(n, _)| *n == lhs || lhs.strip_suffix("_bps") == Some(n))
+            .map(|(_, m)| *m)
+            .ok_or_else(|| format!("unknown metric '{}' (one of: {})",
+                lhs, METRICS.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")))?;
+        Ok(Self { metric, op, value: parse_quantity(rhs)? })
+    }
+
+    fn holds(&self, s: &Summary) -> bool { self.op.holds(self.metric.of(s), self.value) }
+
+    /// Nagios threshold range: alert above `N`, or below `N:`.
+    fn range(&self) -> String {
+        match self.op {
+            Op::Gt | Op::Ge => format!("{}", self.value),
+            Op::Lt | Op::Le => format!("{}:", self.value),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.metric.name(), self.op.symbol(), self.metric.fmt_value(self.value))
+    }
+}
+
+/// What a unit counts: `B` is bytes; `bit`, or a lowercase `b` as in
+/// `Mbps` and `kb/s`, is bits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Counts { Bytes, Bits }
+
+/// `100Mbps` → `(100.0, "m", Some(Bits))`: the number, the lowercased
+/// prefix, and what the unit counts if it says (`/s` and `ps` are dropped).
+/// `None` if there is no number. Shared by every rate and amount option so
+/// they all read `b` and `B` the same way.
+pub fn split_unit(s: &str) -> Option<(f64, String, Option<Counts>)> {
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: f64 = num.parse().ok()?;
+    let unit = unit.trim();
+    let lower = unit.to_ascii_lowercase();
+    let unit = if lower.ends_with("/s") || lower.ends_with("ps") { &unit[..unit.len() - 2] } else { unit };
+    let (prefix, counts) = if unit.to_ascii_lowercase().ends_with("bit") {
+        (&unit[..unit.len() - 3], Some(Counts::Bits))
+    } else if let Some(p) = unit.strip_suffix('B') {
+        (p, Some(Counts::Bytes))
+    } else if let Some(p) = unit.strip_suffix('b') {
+        (p, Some(Counts::Bits))
+    } else {
+        (unit, None)
+    };
+    Some((n, prefix.to_ascii_lowercase(), counts))
+}
+
+/// `100MB/s`, `1.5 GB`, `800Mbit/s`, `100Mbps`, `42` in bytes. Byte units
+/// (and bare numbers) are binary (1024) multiples to match the rest of the
+/// display; bit units are decimal, the way link speeds are quoted, and
+/// divided by 8.
+pub fn parse_quantity(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let (n, prefix, counts) = split_unit(s).ok_or_else(|| format!("invalid number in '{}'", s))?;
+    let bits = counts == Some(Counts::Bits);
+    let base: f64 = if bits { 1000.0 } else { 1024.0 };
+    let power = match prefix.as_str() {
+        ""  => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        _   => return Err(format!("unknown unit in '{}' (use B, KB, MB, GB, TB or Kbit, Mbit, ...)", s)),
+    };
+    Ok(n * base.powi(power) / if bits { 8.0 } else { 1.0 })
+}
+
+// ── Evaluation ────────────────────────────────────────────────────────────────
+
+pub struct Verdict<'a> {
+    pub code:     i32,
+    pub critical: Vec<&'a Condition>,
+    pub warning:  Vec<&'a Condition>,
+}
+
+impl Verdict<'_> {
+    fn label(&self) -> &'static str {
+        match self.code { EXIT_OK => "OK", EXIT_WARNING => "WARNING", EXIT_CRITICAL => "CRITICAL", _ => "UNKNOWN" }
+    }
+}
+
+pub fn evaluate<'a>(s: &Summary, warn: &'a [Condition], crit: &'a [Condition]) -> Verdict<'a> {
+    let critical: Vec<_> = crit.iter().filter(|c| c.holds(s)).collect();
+    let warning:  Vec<_> = warn.iter().filter(|c| c.holds(s)).collect();
+    let code = if !critical.is_empty() { EXIT_CRITICAL } else if !warning.is_empty() { EXIT_WARNING } else { EXIT_OK };
+    Verdict { code, critical, warning }
+}
+
+/// One failed condition with the measured value, for stderr / status text.
+pub fn describe(c: &Condition, s: &Summary) -> String {
+    format!("{} = {} ({})", c.metric.name(), c.metric.fmt_value(c.metric.of(s)), c)
+}
+
+/// `BANDWIDTH OK - eth0 rx=1.20 MB/s tx=3.00 KB/s | avg_rx=1258291.2;;104857600;0 ...`
+pub fn nagios_line(s: &Summary, v: &Verdict, warn: &[Condition], crit: &[Condition]) -> String {
+    let text = if v.code == EXIT_OK {
+        format!("{} rx={} tx={}", s.interface,
+            Metric::AvgRx.fmt_value(s.avg_rx_bps), Metric::AvgTx.fmt_value(s.avg_tx_bps))
+    } else {
+        v.critical.iter().chain(&v.warning).map(|c| describe(c, s)).collect::<Vec<_>>().join(", ")
+    };
+    // Plugins allow one threshold per perf label; the first matching wins.
+    let range = |list: &[Condition], m: Metric| list.iter().find(|c| c.metric == m).map(Condition::range).unwrap_or_default();
+    let perf = METRICS.iter().map(|(name, m)| {
+        let value = if m.is_bytes() { format!("{}B", m.of(s)) } else { format!("{:.2}", m.of(s)) };
+        format!("{}={};{};{};0", name, value, range(warn, *m), range(crit, *m))
+    }).collect::<Vec<_>>().join(" ");
+    format!("BANDWIDTH {} - {} | {}", v.label(), text, perf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: f64 = 1024.0 * 1024.0;
+
+    #[test]
+    fn quantities() {
+        assert_eq!(parse_quantity("42"), Ok(42.0));
+        assert_eq!(parse_quantity("100MB/s"), Ok(100.0 * MIB));
+        assert_eq!(parse_quantity("1.5 GB"), Ok(1.5 * 1024.0 * MIB));
+        assert_eq!(parse_quantity("2kB"), Ok(2048.0));
+        assert_eq!(parse_quantity("2M"), Ok(2.0 * MIB));
+        assert_eq!(parse_quantity("1TB"), Ok(1024.0 * 1024.0 * MIB));
+        assert_eq!(parse_quantity("800Mbit/s"), Ok(100e6));
+        assert_eq!(parse_quantity("8kbit"), Ok(1000.0));
+        assert_eq!(parse_quantity("1Gbitps"), Ok(125e6));
+        assert_eq!(parse_quantity("16bit"), Ok(2.0));
+        assert_eq!(parse_quantity("1Gbit"), Ok(125e6));
+        assert_eq!(parse_quantity("100MBps"), Ok(100.0 * MIB));
+        assert!(parse_quantity("1.5 GB/s\u{e9}").is_err());
+        assert!(parse_quantity("MB").is_err());
+        assert!(parse_quantity("1.2.3MB").is_err());
+        assert!(parse_quantity("5PB").is_err());
+    }
+
+    #[test]
+    fn lowercase_b_is_bits() {
+        assert_eq!(parse_quantity("100Mbps"), Ok(12.5e6));
+        assert_eq!(parse_quantity("10kbps"), Ok(1250.0));
+        assert_eq!(parse_quantity("100Mb/s"), Ok(12.5e6));
+        assert_eq!(parse_quantity("100MB/s"), Ok(100.0 * MIB));
+        let c = Condition::parse("avg_rx > 100Mbps").unwrap();
+        assert_eq!(c.value, 12.5e6);
+    }
+
+    #[test]
+    fn conditions() {
+        let c = Condition::parse("avg_rx > 100MB/s").unwrap();
+        assert_eq!((c.metric, c.op, c.value), (Metric::AvgRx, Op::Gt, 100.0 * MIB));
+        let c = Condition::parse("rx_bytes>=2GB").unwrap();
+        assert_eq!((c.metric, c.op, c.value), (Metric::RxBytes, Op::Ge, 2.0 * 1024.0 * MIB));
+        let c = Condition::parse(" PEAK_TX_bps <= 800Mbit/s").unwrap();
+        assert_eq!((c.metric, c.op, c.value), (Metric::PeakTx, Op::Le, 100e6));
+        let c = Condition::parse("p95_tx<1KB/s").unwrap();
+        assert_eq!((c.metric, c.op, c.value), (Metric::P95Tx, Op::Lt, 1024.0));
+        assert_eq!(c.range(), "1024:");
+        assert_eq!(c.to_string(), "p95_tx < 1.00 KB/s");
+
+        assert!(Condition::parse("avg_rx 100").is_err());
+        assert!(Condition::parse("avg > 100").is_err());
+        assert!(Condition::parse("avg_rx > fast").is_err());
+    }
+
+    #[test]
+    fn conditions_round_trip_through_display() {
+        for s in ["avg_rx > 100MB/s", "tx_bytes >= 2GB", "peak_rx < 512KB/s"] {
+            let c = Condition::parse(s).unwrap();
+            assert_eq!(Condition::parse(&c.to_string()).unwrap().value, c.value, "{}", s);
+        }
+    }
+}
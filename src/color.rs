@@ -0,0 +1,36 @@
+// File: src/color.rs
+//! `--color auto|always|never`, shared by all three binaries.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::{stdout, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Resolved once in `main` from `--color` / `NO_COLOR` / stdout being a tty.
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode { Auto, Always, Never }
+
+impl ColorMode {
+    /// `auto` colours only a terminal, and only if `NO_COLOR` is unset or empty.
+    fn enabled(self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never  => false,
+            ColorMode::Auto   => stdout().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+        }
+    }
+
+    /// Decide for the rest of the run.
+    pub fn apply(self) {
+        COLOR.store(self.enabled(), Ordering::Relaxed);
+    }
+}
+
+/// Whether escape codes may be written.
+pub fn enabled() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
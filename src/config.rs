@@ -0,0 +1,437 @@
+// File: src/config.rs
+//! `~/.config/bandwidthmon/config.toml` — defaults, profiles and display
+//! settings.
+//!
+//!   [defaults]                  # any long option, underscores for dashes
+//!   iface   = "eth"
+//!   height  = 15
+//!   summary = true
+//!   history = 600
+//!   colors  = { download = "green", upload = 208 }
+//!
+//!   [profiles.wan]              # --profile wan, layered over [defaults]
+//!   iface       = "eth0"
+//!   statsd      = "127.0.0.1:8125"
+//!   fail_if     = ["avg_rx > 90MB/s"]
+//!
+//!   [interfaces.eth0]           # display name / colours per interface
+//!   name   = "WAN uplink"
+//!   colors = { download = "cyan" }
+//!
+//! Precedence, highest first: command line, environment, profile,
+//! `[defaults]`, built-in default. Decided per option with clap's
+//! `value_source`, so an explicit `-H 10` always wins even when it equals
+//! the built-in default.
+
+use crate::capture::Group;
+use crate::filter::Filter;
+use crate::output::{self, OutputFormat};
+use crate::{check, link, measure, select, Args, ColorMode, C_CYAN, C_YELLOW};
+use anyhow::{bail, Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+// ── File format ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    defaults:   Settings,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    profiles:   BTreeMap<String, Settings>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    interfaces: BTreeMap<String, InterfaceSettings>,
+}
+
+/// Everything a `[defaults]` or `[profiles.*]` table may set. Every field
+/// is optional; unset fields fall through to the next layer.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    iface:             Option<String>,
+    exclude:           Option<Vec<String>>,
+    height:            Option<usize>,
+    width:             Option<usize>,
+    summary:           Option<bool>,
+    download:          Option<bool>,
+    upload:            Option<bool>,
+    history:           Option<usize>,
+    output:            Option<OutputFormat>,
+    delimiter:         Option<String>,
+    color:             Option<ColorMode>,
+    colors:            Option<Colors>,
+    no_mouse:          Option<bool>,
+    link_speed:        Option<String>,
+    gauge:             Option<bool>,
+    y_max:             Option<String>,
+    netns:             Option<String>,
+    processes:         Option<usize>,
+    capture:           Option<usize>,
+    filter:            Option<String>,
+    group:             Option<Group>,
+    once:              Option<bool>,
+    duration:          Option<String>,
+    warn_if:           Option<Vec<String>>,
+    fail_if:           Option<Vec<String>>,
+    nagios:            Option<bool>,
+    persist:           Option<bool>,
+    record:            Option<PathBuf>,
+    data_dir:          Option<PathBuf>,
+    prometheus_listen: Option<SocketAddr>,
+    statsd:            Option<String>,
+    graphite:          Option<String>,
+    influx_url:        Option<String>,
+    influx_token:      Option<String>,
+    metric_prefix:     Option<String>,
+    metric_host:       Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct InterfaceSettings {
+    name:   Option<String>,
+    colors: Option<Colors>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Colors {
+    download: Option<ColorValue>,
+    upload:   Option<ColorValue>,
+}
+
+/// A 256-colour palette index or one of a few common names.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+enum ColorValue {
+    Index(u8),
+    Name(String),
+}
+
+const COLOR_NAMES: &[(&str, u8)] = &[
+    ("black", 16), ("red", 196), ("green", 46), ("yellow", 226), ("blue", 33),
+    ("magenta", 201), ("cyan", 51), ("white", 15), ("grey", 240), ("gray", 240),
+    ("orange", 208), ("pink", 213),
+];
+
+impl ColorValue {
+    fn index(&self) -> Result<u8> {
+        match self {
+            ColorValue::Index(i) => Ok(*i),
+            ColorValue::Name(n)  => COLOR_NAMES.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(n))
+                .map(|(_, i)| *i)
+                .with_context(|| format!("Unknown colour '{}' (use 0-255 or one of: {})",
+                    n, COLOR_NAMES.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", "))),
+        }
+    }
+}
+
+impl Colors {
+    /// Fill `dl` / `ul` from whichever colours this table sets.
+    fn apply(&self, dl: &mut u8, ul: &mut u8) -> Result<()> {
+        if let Some(ref c) = self.download { *dl = c.index()?; }
+        if let Some(ref c) = self.upload   { *ul = c.index()?; }
+        Ok(())
+    }
+}
+
+impl Settings {
+    /// `other` wins wherever it sets a field.
+    fn overlay(self, other: Settings) -> Settings {
+        macro_rules! pick { ($($f:ident),*) => { Settings { $($f: other.$f.or(self.$f)),* } } }
+        pick!(iface, exclude, height, width, summary, download, upload, history, output, delimiter,
+              color, colors, no_mouse, link_speed, gauge, y_max, netns, processes, capture, filter, group,
+              once, duration, warn_if, fail_if, nagios, persist, record, data_dir,
+              prometheus_listen, statsd, graphite, influx_url, influx_token, metric_prefix, metric_host)
+    }
+}
+
+// ── Display settings ──────────────────────────────────────────────────────────
+
+/// Resolved colours and names used by the live view. Lives on `Args` so every
+/// renderer already has it.
+#[derive(Debug, Clone)]
+pub struct Display {
+    download: u8,
+    upload:   u8,
+    names:    BTreeMap<String, (Option<String>, u8, u8)>,  // iface → name, dl, ul
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self { download: C_CYAN, upload: C_YELLOW, names: BTreeMap::new() }
+    }
+}
+
+impl Display {
+    /// (download, upload) chart colours for `iface`.
+    pub fn colors(&self, iface: &str) -> (u8, u8) {
+        self.names.get(iface).map_or((self.download, self.upload), |(_, dl, ul)| (*dl, *ul))
+    }
+
+    /// `WAN uplink (eth0)` when a display name is configured, else `eth0`.
+    pub fn label(&self, iface: &str) -> String {
+        match self.names.get(iface).and_then(|(n, _, _)| n.as_deref()) {
+            Some(name) => format!("{} ({})", name, iface),
+            None       => iface.to_string(),
+        }
+    }
+}
+
+// ── Loading ───────────────────────────────────────────────────────────────────
+
+/// `$XDG_CONFIG_HOME/bandwidthmon/config.toml`, `~/.config/bandwidthmon/...`,
+/// or `%APPDATA%\bandwidthmon\config.toml` on Windows.
+pub fn default_path() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+    };
+    base.map(|b| b.join("bandwidthmon").join("config.toml"))
+}
+
+fn read(path: &Path) -> Result<ConfigFile> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read config file {}", path.display()))?;
+    toml::from_str(&text).with_context(|| format!("Invalid config file {}", path.display()))
+}
+
+/// Load the config file (explicit `--config`, else the default path if it
+/// exists) and fold it into `args` wherever the option was not given on the
+/// command line or through the environment.
+pub fn apply(args: &mut Args, m: &ArgMatches) -> Result<()> {
+    let file = match (&args.config, default_path()) {
+        (Some(p), _)                => read(p)?,
+        (None, Some(p)) if p.exists() => read(&p)?,
+        _                           => ConfigFile::default(),
+    };
+
+    let mut settings = file.defaults;
+    if let Some(ref name) = args.profile {
+        let Some(profile) = file.profiles.get(name) else {
+            let known: Vec<&str> = file.profiles.keys().map(String::as_str).collect();
+            bail!("Unknown profile '{}' (configured: {})", name,
+                if known.is_empty() { "none".to_string() } else { known.join(", ") });
+        };
+        settings = settings.overlay(profile.clone());
+    }
+
+    let explicit = |id: &str| matches!(m.value_source(id),
+        Some(ValueSource::CommandLine) | Some(ValueSource::EnvVariable));
+
+    macro_rules! set {
+        ($($f:ident),*) => { $(
+            if let Some(v) = settings.$f.clone() { if !explicit(stringify!($f)) { args.$f = v; } }
+        )* };
+    }
+    macro_rules! set_opt {
+        ($($f:ident),*) => { $(
+            if let Some(v) = settings.$f.clone() { if !explicit(stringify!($f)) { args.$f = Some(v); } }
+        )* };
+    }
+    macro_rules! set_export {
+        ($($f:ident),*) => { $(
+            if let Some(v) = settings.$f.clone() { if !explicit(stringify!($f)) { args.export.$f = Some(v); } }
+        )* };
+    }
+    set!(height, width, summary, download, upload, history, output, color, no_mouse, gauge, group, once, nagios, persist);
+    set_opt!(iface, netns, processes, capture, record, data_dir);
+    set_export!(prometheus_listen, statsd, graphite, influx_url, influx_token, metric_host);
+
+    if let Some(ref p) = settings.metric_prefix {
+        if !explicit("metric_prefix") { args.export.metric_prefix = p.clone(); }
+    }
+    if let Some(ref d) = settings.delimiter {
+        if !explicit("delimiter") {
+            args.delimiter = output::parse_delimiter(d).context("Config: delimiter")?;
+        }
+    }
+    if let Some(ref d) = settings.duration {
+        if !explicit("duration") {
+            args.duration = measure::parse_duration(d).map_err(anyhow::Error::msg).context("Config: duration")?;
+        }
+    }
+    if let Some(ref x) = settings.exclude {
+        if !explicit("exclude") {
+            args.exclude = x.iter().map(|p| select::Pattern::parse(p).map_err(anyhow::Error::msg))
+                .collect::<Result<_>>().context("Config: exclude")?;
+        }
+    }
+    if let Some(ref l) = settings.link_speed {
+        if !explicit("link_speed") {
+            args.link_speed = Some(link::parse_speed(l).map_err(anyhow::Error::msg).context("Config: link_speed")?);
+        }
+    }
+    if let Some(ref y) = settings.y_max {
+        if !explicit("y_max") {
+            args.y_max = Some(link::YMax::parse(y).map_err(anyhow::Error::msg).context("Config: y_max")?);
+        }
+    }
+    if let Some(ref f) = settings.filter {
+        if !explicit("filter") {
+            args.filter = Some(Filter::parse(f).map_err(anyhow::Error::msg).context("Config: filter")?);
+        }
+    }
+    let conditions = |list: &[String]| -> Result<Vec<check::Condition>> {
+        list.iter().map(|c| check::Condition::parse(c).map_err(anyhow::Error::msg)).collect()
+    };
+    if let Some(ref c) = settings.warn_if {
+        if !explicit("warn_if") { args.warn_if = conditions(c).context("Config: warn_if")?; }
+    }
+    if let Some(ref c) = settings.fail_if {
+        if !explicit("fail_if") { args.fail_if = conditions(c).context("Config: fail_if")?; }
+    }
+
+    // Display: global colours first, then per-interface overrides on top.
+    let mut display = Display::default();
+    if let Some(ref c) = settings.colors {
+        c.apply(&mut display.download, &mut display.upload).context("Config: colors")?;
+    }
+    for (iface, s) in &file.interfaces {
+        let (mut dl, mut ul) = (display.download, display.upload);
+        if let Some(ref c) = s.colors {
+            c.apply(&mut dl, &mut ul).with_context(|| format!("Config: interfaces.{}.colors", iface))?;
+        }
+        display.names.insert(iface.clone(), (s.name.clone(), dl, ul));
+    }
+    args.display = display;
+    Ok(())
+}
+
+// ── --print-config ────────────────────────────────────────────────────────────
+
+/// The effective settings as a config file: paste it into `config.toml`.
+pub fn print_effective(args: &Args) -> Result<()> {
+    let e = &args.export;
+    let d = &args.display;
+    let color = |i: u8| Some(ColorValue::Index(i));
+    let defaults = Settings {
+        iface:             args.iface.clone(),
+        exclude:           Some(args.exclude.iter().map(ToString::to_string).collect()),
+        height:            Some(args.height),
+        width:             Some(args.width),
+        summary:           Some(args.summary),
+        download:          Some(args.download),
+        upload:            Some(args.upload),
+        history:           Some(args.history),
+        output:            Some(args.output),
+        delimiter:         Some(if args.delimiter == '\t' { "tab".to_string() } else { args.delimiter.to_string() }),
+        color:             Some(args.color),
+        colors:            Some(Colors { download: color(d.download), upload: color(d.upload) }),
+        no_mouse:          Some(args.no_mouse),
+        link_speed:        args.link_speed.map(|b| format!("{}M", b / 1e6)),
+        gauge:             Some(args.gauge),
+        y_max:             args.y_max.map(|y| y.to_string()),
+        netns:             args.netns.clone(),
+        processes:         args.processes,
+        capture:           args.capture,
+        filter:            args.filter.as_ref().map(ToString::to_string),
+        group:             Some(args.group),
+        once:              Some(args.once),
+        duration:          Some(format!("{}s", args.duration.as_secs_f64())),
+        warn_if:           Some(args.warn_if.iter().map(ToString::to_string).collect()),
+        fail_if:           Some(args.fail_if.iter().map(ToString::to_string).collect()),
+        nagios:            Some(args.nagios),
+        persist:           Some(args.persist),
+        record:            args.record.clone(),
+        data_dir:          Some(args.data_dir()),
+        prometheus_listen: e.prometheus_listen,
+        statsd:            e.statsd.clone(),
+        graphite:          e.graphite.clone(),
+        influx_url:        e.influx_url.clone(),
+        influx_token:      None,   // a secret; see the note below
+        metric_prefix:     Some(e.metric_prefix.clone()),
+        metric_host:       Some(e.host()),
+    };
+    let interfaces = d.names.iter().map(|(iface, (name, dl, ul))| (iface.clone(), InterfaceSettings {
+        name:   name.clone(),
+        colors: Some(Colors { download: color(*dl), upload: color(*ul) }),
+    })).collect();
+    let file = ConfigFile { defaults, profiles: BTreeMap::new(), interfaces };
+
+    let source = match (&args.config, default_path()) {
+        (Some(p), _)                => p.display().to_string(),
+        (None, Some(p)) if p.exists() => p.display().to_string(),
+        _                           => "none".to_string(),
+    };
+    println!("# Effective settings (config: {}, profile: {})", source, args.profile.as_deref().unwrap_or("none"));
+    if e.influx_token.is_some() {
+        println!("# influx_token is set but not printed; copy it over yourself");
+    }
+    print!("{}", toml::to_string_pretty(&file)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+    use std::fs;
+
+    const CONFIG: &str = r#"
+[defaults]
+height  = 15
+history = 600
+iface   = "eth"
+
+[profiles.wan]
+height     = 20
+iface      = "eth0"
+link_speed = "100Mbps"
+"#;
+
+    /// `args` parsed against CONFIG as the config file, then folded in.
+    fn load(name: &str, args: &[&str]) -> Result<Args> {
+        let path = std::env::temp_dir().join(format!("bandwidthmon-test-{}-{}.toml", name, std::process::id()));
+        fs::write(&path, CONFIG).unwrap();
+        let argv = ["bandwidthmon", "--config", path.to_str().unwrap()].into_iter().chain(args.iter().copied());
+        let m = Args::command().try_get_matches_from(argv).unwrap();
+        let mut parsed = Args::from_arg_matches(&m).unwrap();
+        let r = apply(&mut parsed, &m);
+        fs::remove_file(&path).unwrap();
+        r.map(|()| parsed)
+    }
+
+    #[test]
+    fn defaults_fill_unset_options() {
+        let a = load("defaults", &[]).unwrap();
+        assert_eq!((a.height, a.history, a.iface.as_deref()), (15, 600, Some("eth")));
+    }
+
+    #[test]
+    fn profile_overrides_defaults() {
+        let a = load("profile", &["--profile", "wan"]).unwrap();
+        assert_eq!((a.height, a.history, a.iface.as_deref()), (20, 600, Some("eth0")));
+        assert_eq!(a.link_speed, Some(1e8));
+    }
+
+    #[test]
+    fn command_line_wins_even_at_the_default() {
+        let a = load("explicit", &["--profile", "wan", "-H", "10", "-i", "wlan0"]).unwrap();
+        assert_eq!((a.height, a.history, a.iface.as_deref()), (10, 600, Some("wlan0")));
+        let a = load("explicit-link", &["--profile", "wan", "--link-speed", "1G"]).unwrap();
+        assert_eq!(a.link_speed, Some(1e9));
+    }
+
+    #[test]
+    fn unknown_profile_is_an_error() {
+        let e = load("unknown", &["--profile", "lan"]).expect_err("profile 'lan' should be refused");
+        assert_eq!(e.to_string(), "Unknown profile 'lan' (configured: wan)");
+    }
+
+    #[test]
+    fn overlay_prefers_the_upper_layer() {
+        let lower = Settings { height: Some(15), history: Some(600), ..Settings::default() };
+        let upper = Settings { height: Some(20), summary: Some(true), ..Settings::default() };
+        let s = lower.overlay(upper);
+        assert_eq!((s.height, s.history, s.summary), (Some(20), Some(600), Some(true)));
+    }
+}
@@ -0,0 +1,272 @@
+// File: src/daemon.rs
+//! `bandwidthmon record` — headless sampler for systemd and friends.
+//!
+//! No raw mode, no screen: counters for every selected interface are written
+//! to the history store once per `--interval`. SIGINT / SIGTERM / SIGHUP stop
+//! the loop after a final partial sample, so a restart never loses traffic.
+//! Runs in the foreground (systemd `Type=simple`); the pid file is for tools
+//! that still expect one.
+//!
+//! Exit codes:
+//!   0  stopped by a signal
+//!   1  sampling or store failure
+//!   2  another recorder owns --pid-file
+//!   3  --status: no recorder running (LSB "program is not running")
+
+use crate::export::{ExportArgs, Exporters};
+use crate::sampler::Sampler;
+use crate::select;
+use crate::store::{HistoryStore, Record};
+use crate::unix_now;
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const EXIT_ERROR:       i32 = 1;
+const EXIT_RUNNING:     i32 = 2;
+const EXIT_NOT_RUNNING: i32 = 3;
+
+/// Longest we sleep between checks of the stop flag.
+const TICK: Duration = Duration::from_millis(200);
+
+// ── CLI ───────────────────────────────────────────────────────────────────────
+
+#[derive(clap::Args, Debug)]
+pub struct RecordArgs {
+    /// Interfaces to record: name or substring, glob or re:REGEX (repeatable; default: all)
+    #[arg(short, long, value_parser = select::Pattern::parse)]
+    pub iface: Vec<select::Pattern>,
+
+    /// Seconds between stored samples
+    #[arg(short = 'n', long, default_value_t = 60)]
+    pub interval: u64,
+
+    /// Write the process id to FILE while running
+    #[arg(long, value_name = "FILE")]
+    pub pid_file: Option<PathBuf>,
+
+    /// Check whether the recorder owning --pid-file is alive and exit
+    #[arg(long, requires = "pid_file")]
+    pub status: bool,
+}
+
+// ── Pid file ──────────────────────────────────────────────────────────────────
+
+/// The pid file, locked for as long as the recorder runs. The lock, not the
+/// pid written in it, says whether a recorder is alive: a file left behind
+/// by a crash is free to take over even if its pid now belongs to some
+/// other process. Removed when dropped — including on the error path.
+struct PidFile {
+    path:  PathBuf,
+    _file: File,   // holds the lock
+}
+
+impl PidFile {
+    /// Lock `path` and write our pid to it; `None` if a live recorder holds it.
+    fn acquire(path: &Path) -> Result<Option<Self>> {
+        let context = || format!("Cannot write pid file {}", path.display());
+        loop {
+            let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false)
+                .open(path).with_context(context)?;
+            if !sys::try_lock(&file, path).with_context(context)? { return Ok(None); }
+            // A recorder stopping right now may have removed the file we opened.
+            if !sys::same_file(&file, path) { continue; }
+            file.set_len(0).with_context(context)?;
+            file.write_all(format!("{}\n", std::process::id()).as_bytes()).with_context(context)?;
+            return Ok(Some(Self { path: path.to_path_buf(), _file: file }));
+        }
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) { let _ = fs::remove_file(&self.path); }
+}
+
+/// Whether a live recorder holds `path`. Probing takes the lock of a stale
+/// file for a moment, and never creates one.
+fn running(path: &Path) -> Result<bool> {
+    match File::open(path) {
+        Ok(file) => Ok(!sys::try_lock(&file, path)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The pid written in `path`, for messages.
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// "pid 1234", or "pid unknown" while the holder is still writing it.
+fn describe(path: &Path) -> String {
+    read_pid(path).map_or_else(|| "pid unknown".to_string(), |pid| format!("pid {}", pid))
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    /// `flock(LOCK_EX | LOCK_NB)`; `false` if another process holds it.
+    /// Released when `file` is closed, however the process ends.
+    pub fn try_lock(file: &File, _path: &Path) -> io::Result<bool> {
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 { return Ok(true); }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() == Some(libc::EWOULDBLOCK) { Ok(false) } else { Err(e) }
+    }
+
+    /// Whether `path` still names the open `file`.
+    pub fn same_file(file: &File, path: &Path) -> bool {
+        match (file.metadata(), std::fs::metadata(path)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+
+    /// No advisory locks without extra bindings: an existing, non-empty
+    /// file counts as held.
+    pub fn try_lock(_file: &File, path: &Path) -> io::Result<bool> {
+        Ok(std::fs::metadata(path).map(|m| m.len() == 0).unwrap_or(true))
+    }
+
+    pub fn same_file(_file: &File, _path: &Path) -> bool { true }
+}
+
+// ── Recorder ──────────────────────────────────────────────────────────────────
+
+fn wanted(name: &str, patterns: &[select::Pattern]) -> bool {
+    patterns.is_empty() || patterns.iter().any(|p| p.selects(name))
+}
+
+fn store_sample(sampler: &mut Sampler, store: &mut HistoryStore, exporters: &mut Exporters, patterns: &[select::Pattern]) -> Result<()> {
+    let ts = unix_now();
+    let samples: Vec<_> = sampler.sample().into_iter().filter(|(n, _)| wanted(n, patterns)).collect();
+    exporters.publish(&samples);
+    for (name, s) in samples {
+        if s.elapsed < 0.001 { continue; }
+        store.append(&Record { ts, interface: name, rx: s.rx_delta, tx: s.tx_delta, secs: s.elapsed })?;
+    }
+    Ok(())
+}
+
+fn record(args: &RecordArgs, data_dir: &Path, export: &ExportArgs) -> Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || { r.store(false, Ordering::SeqCst); })?;
+
+    let mut store     = HistoryStore::open(data_dir)?;
+    let mut sampler   = Sampler::new();
+    let mut exporters = Exporters::start(export, crate::DEFAULT_HISTORY)?;
+
+    let names: Vec<String> = sampler.sample().into_iter().map(|(n, _)| n).collect();
+    for p in &args.iface {
+        if !names.iter().any(|n| wanted(n, std::slice::from_ref(p))) {
+            eprintln!("bandwidthmon: warning: no interface matches '{}' yet", p);
+        }
+    }
+    eprintln!("bandwidthmon: recording {} every {}s to {}",
+        if args.iface.is_empty() { "all interfaces".to_string() } else {
+            args.iface.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+        },
+        args.interval.max(1), data_dir.display());
+
+    let period = Duration::from_secs(args.interval.max(1));
+    let mut next = Instant::now() + period;
+
+    while running.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        if now >= next {
+            store_sample(&mut sampler, &mut store, &mut exporters, &args.iface)?;
+            next += period;
+            // Suspended laptop / stalled clock: don't fire a burst of catch-up samples.
+            if next < now { next = now + period; }
+            continue;
+        }
+        std::thread::sleep(TICK.min(next - now));
+    }
+
+    // Final partial interval so nothing between the last tick and the signal is lost.
+    store_sample(&mut sampler, &mut store, &mut exporters, &args.iface)?;
+    eprintln!("bandwidthmon: stopped");
+    Ok(())
+}
+
+// ── Entry ─────────────────────────────────────────────────────────────────────
+
+pub fn run(args: &RecordArgs, data_dir: &Path, export: &ExportArgs) -> Result<()> {
+    if args.status {
+        let path = args.pid_file.as_deref().context("--status needs --pid-file")?;
+        if running(path)? {
+            println!("running ({})", describe(path));
+            return Ok(());
+        }
+        println!("not running");
+        std::process::exit(EXIT_NOT_RUNNING);
+    }
+
+    // Scope the pid-file guard so it is removed before process::exit.
+    let result = (|| {
+        let _pid = match args.pid_file.as_deref() {
+            Some(path) => match PidFile::acquire(path)? {
+                Some(pid) => Some(pid),
+                None => {
+                    eprintln!("bandwidthmon: already running ({})", describe(path));
+                    std::process::exit(EXIT_RUNNING);
+                }
+            },
+            None => None,
+        };
+        record(args, data_dir, export)
+    })();
+
+    if let Err(e) = result {
+        eprintln!("bandwidthmon: error: {:#}", e);
+        std::process::exit(EXIT_ERROR);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bandwidthmon-test-{}-{}.pid", name, std::process::id()))
+    }
+
+    #[test]
+    fn second_recorder_is_refused() {
+        let path = temp_path("held");
+        let first = PidFile::acquire(&path).unwrap().unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+        assert!(running(&path).unwrap());
+        assert!(PidFile::acquire(&path).unwrap().is_none());
+        drop(first);
+        assert!(!path.exists());
+        assert!(!running(&path).unwrap());
+    }
+
+    #[test]
+    fn stale_file_is_taken_over() {
+        // Left by a crash, with a pid that now belongs to something else.
+        let path = temp_path("stale");
+        fs::write(&path, "1\n").unwrap();
+        assert!(!running(&path).unwrap());
+        let _pid = PidFile::acquire(&path).unwrap().unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+    }
+}
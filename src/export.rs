@@ -0,0 +1,103 @@
+// File: src/export.rs
+//! Fan-out of each interval's samples to the optional metric exporters.
+//!
+//! Every mode (TUI, `--output`, `record`) calls `Exporters::publish` once per
+//! interval with the samples of *all* interfaces; each exporter decides what
+//! to do with them. Exporters never fail the caller — a dead endpoint must
+//! not take the UI or the recorder down with it.
+
+use crate::influx::{self, InfluxWriter};
+use crate::output::SampleRecord;
+use crate::prometheus::{self, Registry};
+use crate::push::{self, Protocol, Pusher};
+use crate::BandwidthStats;
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ExportArgs {
+    /// Serve Prometheus metrics on ADDR (e.g. 127.0.0.1:9177) at /metrics
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub prometheus_listen: Option<SocketAddr>,
+
+    /// Push rates and byte counters to a StatsD server (UDP HOST:PORT)
+    #[arg(long, global = true, value_name = "HOST:PORT")]
+    pub statsd: Option<String>,
+
+    /// Push rates and byte totals to a Graphite plaintext listener (TCP HOST:PORT)
+    #[arg(long, global = true, value_name = "HOST:PORT")]
+    pub graphite: Option<String>,
+
+    /// Batch samples to an InfluxDB-compatible write endpoint
+    /// (e.g. http://127.0.0.1:8086/write?db=net)
+    #[arg(long, global = true, value_name = "URL")]
+    pub influx_url: Option<String>,
+
+    /// API token sent as "Authorization: Token ..." to --influx-url
+    #[arg(long, global = true, value_name = "TOKEN", env = "BANDWIDTHMON_INFLUX_TOKEN", hide_env_values = true)]
+    pub influx_token: Option<String>,
+
+    /// First component of pushed metric names / Influx measurement name
+    #[arg(long, global = true, default_value = "bandwidthmon", value_name = "PREFIX")]
+    pub metric_prefix: String,
+
+    /// Host component / tag of pushed metrics (default: system host name)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub metric_host: Option<String>,
+}
+
+impl ExportArgs {
+    pub fn host(&self) -> String {
+        self.metric_host.clone().unwrap_or_else(push::default_host)
+    }
+}
+
+#[derive(Default)]
+pub struct Exporters {
+    prometheus: Option<Arc<Mutex<Registry>>>,
+    pushers:    Vec<Pusher>,
+    influx:     Option<(InfluxWriter, String, String)>,  // writer, measurement, host
+}
+
+impl Exporters {
+    /// Start every configured exporter. `window` is the number of recent
+    /// samples kept for percentiles (the `--history` size).
+    pub fn start(args: &ExportArgs, window: usize) -> Result<Self> {
+        let mut ex = Self::default();
+        if let Some(addr) = args.prometheus_listen {
+            let reg = Arc::new(Mutex::new(Registry::new(window)));
+            prometheus::serve(addr, reg.clone())?;
+            ex.prometheus = Some(reg);
+        }
+        let host = args.host();
+        if let Some(ref ep) = args.statsd {
+            ex.pushers.push(Pusher::start(Protocol::Statsd, ep, &args.metric_prefix, &host)?);
+        }
+        if let Some(ref ep) = args.graphite {
+            ex.pushers.push(Pusher::start(Protocol::Graphite, ep, &args.metric_prefix, &host)?);
+        }
+        if let Some(ref url) = args.influx_url {
+            let w = InfluxWriter::start(url, args.influx_token.clone())?;
+            ex.influx = Some((w, args.metric_prefix.clone(), host));
+        }
+        Ok(ex)
+    }
+
+    pub fn publish(&mut self, samples: &[(String, BandwidthStats)]) {
+        if let Some(ref reg) = self.prometheus {
+            if let Ok(mut r) = reg.lock() { r.observe(samples); }
+        }
+        for p in &self.pushers {
+            p.push(samples);
+        }
+        if let Some((ref w, ref measurement, ref host)) = self.influx {
+            let now = chrono::Local::now();
+            let lines = samples.iter()
+                .filter(|(_, s)| s.elapsed >= 0.001)
+                .map(|(n, s)| influx::line(measurement, host, &SampleRecord::from_stats(&now, n, s)))
+                .collect();
+            w.send(lines);
+        }
+    }
+}
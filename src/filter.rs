@@ -0,0 +1,336 @@
+// File: src/filter.rs
+//! `--filter`: a tcpdump-like subset of BPF expressions, evaluated in
+//! userspace on each captured packet.
+//!
+//!   port 443                      tcp and not port 22
+//!   host 10.0.0.5                 src net 192.168.0.0/16
+//!   udp portrange 5000-5100       (port 80 or port 443) and dst host 1.2.3.4
+//!
+//! Primitives: `host ADDR`, `net ADDR/LEN`, `port N`, `portrange A-B`, each
+//! optionally prefixed by `src` or `dst`; protocols `tcp`, `udp`, `icmp`,
+//! `icmp6`, `ip`, `ip6`. Combine with `and`/`&&`, `or`/`||`, `not`/`!` and
+//! parentheses; adjacent primitives are and-ed, so `tcp port 80` works.
+
+use crate::packet::{Packet, PROTO_ICMP, PROTO_ICMPV6, PROTO_TCP, PROTO_UDP};
+use std::fmt;
+use std::net::IpAddr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Dir { Src, Dst, Any }
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Host(Dir, IpAddr),
+    Net(Dir, Net),
+    Ports(Dir, u16, u16),
+    Proto(u8),
+    V4,
+    V6,
+}
+
+/// A parsed filter; displays as the text it was parsed from.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    text: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let tokens = tokenize(s);
+        if tokens.is_empty() { return Err("empty filter".to_string()); }
+        let mut p = Parser { tokens, pos: 0 };
+        let expr = p.expr()?;
+        if let Some(t) = p.peek() {
+            return Err(format!("unexpected '{}' in filter", t));
+        }
+        Ok(Self { text: s.trim().to_string(), expr })
+    }
+
+    pub fn matches(&self, p: &Packet) -> bool { self.expr.matches(p) }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.text) }
+}
+
+/// An address with a prefix length: `10.0.0.0/8`, `fe80::/10`, or a bare
+/// address meaning just that host.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Net {
+    addr: IpAddr,
+    len:  u8,
+}
+
+impl Net {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (addr, len) = s.split_once('/').unwrap_or((s, ""));
+        let addr = parse_addr(addr)?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let len = if len.is_empty() { max } else {
+            len.parse::<u8>().ok().filter(|l| *l <= max).ok_or_else(|| format!("invalid prefix length in '{}'", s))?
+        };
+        Ok(Self { addr, len })
+    }
+
+    pub fn contains(&self, a: IpAddr) -> bool {
+        match (a, self.addr) {
+            (IpAddr::V4(a), IpAddr::V4(n)) => {
+                let mask = u32::MAX.checked_shl(32 - self.len as u32).unwrap_or(0);
+                u32::from(a) & mask == u32::from(n) & mask
+            }
+            (IpAddr::V6(a), IpAddr::V6(n)) => {
+                let mask = u128::MAX.checked_shl(128 - self.len as u32).unwrap_or(0);
+                u128::from(a) & mask == u128::from(n) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Net {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}/{}", self.addr, self.len) }
+}
+
+impl Expr {
+    fn matches(&self, p: &Packet) -> bool {
+        let either = |d: Dir, f: &dyn Fn(IpAddr, u16) -> bool| match d {
+            Dir::Src => f(p.src, p.sport),
+            Dir::Dst => f(p.dst, p.dport),
+            Dir::Any => f(p.src, p.sport) || f(p.dst, p.dport),
+        };
+        match self {
+            Expr::Or(a, b)  => a.matches(p) || b.matches(p),
+            Expr::And(a, b) => a.matches(p) && b.matches(p),
+            Expr::Not(e)    => !e.matches(p),
+            Expr::Host(d, h)        => either(*d, &|a, _| a == *h),
+            Expr::Net(d, net)       => either(*d, &|a, _| net.contains(a)),
+            Expr::Ports(d, lo, hi)  => matches!(p.proto, PROTO_TCP | PROTO_UDP)
+                && either(*d, &|_, port| (*lo..=*hi).contains(&port)),
+            Expr::Proto(n) => p.proto == *n,
+            Expr::V4       => p.src.is_ipv4(),
+            Expr::V6       => p.src.is_ipv6(),
+        }
+    }
+}
+
+// ── Parser ────────────────────────────────────────────────────────────────────
+
+fn tokenize(s: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    for c in s.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' || c == '!' {
+            if !cur.is_empty() { out.push(std::mem::take(&mut cur)); }
+            if !c.is_whitespace() { out.push(c.to_string()); }
+        } else {
+            cur.push(c);
+        }
+    }
+    if !cur.is_empty() { out.push(cur); }
+    out
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos:    usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> { self.tokens.get(self.pos).map(String::as_str) }
+
+    fn next(&mut self, what: &str) -> Result<String, String> {
+        let t = self.tokens.get(self.pos).cloned().ok_or_else(|| format!("filter ends where {} was expected", what))?;
+        self.pos += 1;
+        Ok(t)
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut e = self.term()?;
+        while matches!(self.peek(), Some("or" | "||")) {
+            self.pos += 1;
+            e = Expr::Or(Box::new(e), Box::new(self.term()?));
+        }
+        Ok(e)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut e = self.factor()?;
+        loop {
+            match self.peek() {
+                Some("and" | "&&") => self.pos += 1,
+                Some("or" | "||" | ")") | None => return Ok(e),
+                Some(_) => {}  // implicit and: `tcp port 80`
+            }
+            e = Expr::And(Box::new(e), Box::new(self.factor()?));
+        }
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.next("a filter primitive")?.as_str() {
+            "not" | "!" => Ok(Expr::Not(Box::new(self.factor()?))),
+            "(" => {
+                let e = self.expr()?;
+                match self.next("')'")?.as_str() {
+                    ")" => Ok(e),
+                    t   => Err(format!("expected ')' but found '{}'", t)),
+                }
+            }
+            "tcp"   => Ok(Expr::Proto(PROTO_TCP)),
+            "udp"   => Ok(Expr::Proto(PROTO_UDP)),
+            "icmp"  => Ok(Expr::Proto(PROTO_ICMP)),
+            "icmp6" => Ok(Expr::Proto(PROTO_ICMPV6)),
+            "ip"    => Ok(Expr::V4),
+            "ip6"   => Ok(Expr::V6),
+            "src" => self.qualified(Dir::Src),
+            "dst" => self.qualified(Dir::Dst),
+            t => self.qualified_by(Dir::Any, t),
+        }
+    }
+
+    /// After `src` / `dst`: a primitive, or a bare address meaning `host`.
+    fn qualified(&mut self, dir: Dir) -> Result<Expr, String> {
+        if let Some(t) = self.peek().filter(|t| matches!(*t, "host" | "net" | "port" | "portrange")) {
+            let t = t.to_string();
+            self.pos += 1;
+            self.qualified_by(dir, &t)
+        } else {
+            Ok(Expr::Host(dir, parse_addr(&self.next("an address")?)?))
+        }
+    }
+
+    /// The primitive introduced by `keyword`, which has been consumed.
+    fn qualified_by(&mut self, dir: Dir, keyword: &str) -> Result<Expr, String> {
+        match keyword {
+            "host" => Ok(Expr::Host(dir, parse_addr(&self.next("an address after 'host'")?)?)),
+            "net"  => {
+                let t = self.next("a network after 'net'")?;
+                Ok(Expr::Net(dir, Net::parse(&t)?))
+            }
+            "port" => {
+                let p = parse_port(&self.next("a port number after 'port'")?)?;
+                Ok(Expr::Ports(dir, p, p))
+            }
+            "portrange" => {
+                let t = self.next("A-B after 'portrange'")?;
+                let (a, b) = t.split_once('-').ok_or_else(|| format!("expected A-B, found '{}'", t))?;
+                let (a, b) = (parse_port(a)?, parse_port(b)?);
+                if a > b { return Err(format!("portrange '{}' runs backwards", t)); }
+                Ok(Expr::Ports(dir, a, b))
+            }
+            t => Err(format!("unknown filter primitive '{}' (host, net, port, portrange, tcp, udp, icmp, ip, ip6)", t)),
+        }
+    }
+}
+
+fn parse_addr(s: &str) -> Result<IpAddr, String> {
+    s.parse().map_err(|_| format!("invalid address '{}'", s))
+}
+
+fn parse_port(s: &str) -> Result<u16, String> {
+    s.parse().map_err(|_| format!("invalid port '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkt(proto: u8, src: &str, sport: u16, dst: &str, dport: u16) -> Packet {
+        Packet { proto, src: src.parse().unwrap(), dst: dst.parse().unwrap(), sport, dport, len: 100 }
+    }
+
+    fn tcp(sport: u16, dport: u16) -> Packet { pkt(PROTO_TCP, "10.0.0.1", sport, "10.0.0.2", dport) }
+    fn udp(sport: u16, dport: u16) -> Packet { pkt(PROTO_UDP, "10.0.0.1", sport, "10.0.0.2", dport) }
+
+    fn matches(f: &str, p: &Packet) -> bool { Filter::parse(f).unwrap().matches(p) }
+
+    #[test]
+    fn primitives() {
+        assert!(matches("tcp", &tcp(1, 2)));
+        assert!(!matches("udp", &tcp(1, 2)));
+        assert!(matches("port 443", &tcp(50000, 443)));
+        assert!(matches("port 443", &tcp(443, 50000)));
+        assert!(!matches("dst port 443", &tcp(443, 50000)));
+        assert!(matches("src port 443", &tcp(443, 50000)));
+        assert!(matches("portrange 5000-5100", &udp(1, 5050)));
+        assert!(!matches("portrange 5000-5100", &udp(1, 5101)));
+        assert!(matches("host 10.0.0.2", &tcp(1, 2)));
+        assert!(matches("src 10.0.0.1", &tcp(1, 2)));
+        assert!(!matches("dst host 10.0.0.1", &tcp(1, 2)));
+        assert!(matches("net 10.0.0.0/8", &tcp(1, 2)));
+        assert!(!matches("src net 192.168.0.0/16", &tcp(1, 2)));
+        assert!(matches("ip", &tcp(1, 2)));
+        assert!(!matches("ip6", &tcp(1, 2)));
+    }
+
+    #[test]
+    fn ports_only_match_tcp_and_udp() {
+        assert!(!matches("port 0", &pkt(PROTO_ICMP, "10.0.0.1", 0, "10.0.0.2", 0)));
+    }
+
+    #[test]
+    fn ipv6_hosts_and_nets() {
+        let p = pkt(PROTO_ICMPV6, "fe80::1", 0, "2001:db8::5", 0);
+        assert!(matches("icmp6 and ip6", &p));
+        assert!(matches("src net fe80::/10", &p));
+        assert!(matches("dst host 2001:db8::5", &p));
+        assert!(!matches("net 10.0.0.0/8", &p));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // tcp or (udp and port 53)
+        assert!(matches("tcp or udp and port 53", &tcp(1, 2)));
+        assert!(matches("tcp or udp and port 53", &udp(1, 53)));
+        assert!(!matches("tcp or udp and port 53", &udp(1, 54)));
+        // (tcp or udp) and port 53
+        assert!(!matches("(tcp or udp) and port 53", &tcp(1, 2)));
+        assert!(matches("(tcp || udp) && port 53", &tcp(1, 53)));
+    }
+
+    #[test]
+    fn adjacent_primitives_are_anded() {
+        assert!(matches("tcp port 80", &tcp(1, 80)));
+        assert!(!matches("tcp port 80", &udp(1, 80)));
+        assert!(matches("udp dst port 53 src host 10.0.0.1", &udp(1, 53)));
+    }
+
+    #[test]
+    fn negation() {
+        assert!(!matches("not port 22", &tcp(22, 1)));
+        assert!(matches("! port 22", &tcp(23, 1)));
+        assert!(matches("tcp and not (port 22 or port 23)", &tcp(80, 1)));
+        assert!(!matches("!(tcp)", &tcp(80, 1)));
+        assert!(matches("not not tcp", &tcp(80, 1)));
+    }
+
+    #[test]
+    fn errors() {
+        for bad in ["", "   ", "port", "port abc", "port 70000", "(tcp", "tcp )", "tcp or", "bogus",
+                    "net 10.0.0.0/33", "host 10.0.0", "portrange 5", "portrange 90-80", "src"] {
+            assert!(Filter::parse(bad).is_err(), "'{}' should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn displays_as_typed() {
+        assert_eq!(Filter::parse("  tcp  and port 443 ").unwrap().to_string(), "tcp  and port 443");
+    }
+
+    #[test]
+    fn nets() {
+        let n = Net::parse("192.168.1.77/24").unwrap();
+        assert!(n.contains("192.168.1.1".parse().unwrap()));
+        assert!(!n.contains("192.168.2.1".parse().unwrap()));
+        assert!(!n.contains("::1".parse().unwrap()));
+        assert!(Net::parse("0.0.0.0/0").unwrap().contains("8.8.8.8".parse().unwrap()));
+        assert!(Net::parse("10.0.0.1").unwrap().contains("10.0.0.1".parse().unwrap()));
+        assert!(!Net::parse("10.0.0.1").unwrap().contains("10.0.0.2".parse().unwrap()));
+        assert!(Net::parse("::/0").unwrap().contains("2001:db8::1".parse().unwrap()));
+        assert!(Net::parse("fe80::/129").is_err());
+        assert_eq!(Net::parse("10.1.2.3").unwrap().to_string(), "10.1.2.3/32");
+    }
+}
@@ -0,0 +1,423 @@
+// File: src/iface.rs
+//! `--list`: every interface with its addresses, link details and counters.
+//!
+//!   bandwidthmon -l
+//!   bandwidthmon -l --kind physical,wireless --up --sort total
+//!   bandwidthmon -l --json | jq '.[] | select(.speed_mbps > 1000)'
+//!
+//! MAC, MTU and addresses come from sysinfo on every platform. On Linux,
+//! `/sys/class/net/<if>` adds the operational state, speed and duplex,
+//! master device and enough hints to tell the interface type; the driver is
+//! asked for with the ethtool ioctl, which also names virtual drivers (veth,
+//! bridge, tun) that have no `device` link in sysfs. Inside another
+//! namespace (`--pid` / `--netns`) sysfs still shows our own, so only the
+//! counters are listed there.
+
+use crate::output::{self, InterfaceRecord, OutputFormat};
+use crate::sampler::Sampler;
+use crate::select;
+use crate::{fmt_total, styled, Args, C_CYAN, C_GREY, C_WHITE};
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::net::IpAddr;
+use sysinfo::NetworkData;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Physical,
+    Wireless,
+    Loopback,
+    Bridge,
+    Bond,
+    Vlan,
+    Veth,
+    Tunnel,
+    Virtual,
+    #[default]
+    Unknown,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Physical => "physical", Kind::Wireless => "wireless", Kind::Loopback => "loopback",
+            Kind::Bridge   => "bridge",   Kind::Bond     => "bond",     Kind::Vlan     => "vlan",
+            Kind::Veth     => "veth",     Kind::Tunnel   => "tunnel",   Kind::Virtual  => "virtual",
+            Kind::Unknown  => "unknown",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SortKey { Name, Type, State, Speed, Rx, Tx, Total }
+
+/// Everything `--list` knows about an interface besides its counters.
+/// Fields the platform doesn't report stay empty.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Details {
+    #[serde(rename = "type")]
+    pub kind:       Kind,
+    pub operstate:  Option<String>,
+    pub mac:        Option<String>,
+    pub mtu:        Option<u64>,
+    pub speed_mbps: Option<u64>,
+    pub duplex:     Option<String>,
+    pub driver:     Option<String>,
+    pub master:     Option<String>,
+    pub ipv4:       Vec<String>,   // address/prefix
+    pub ipv6:       Vec<String>,
+    #[serde(skip)]
+    admin_up:       Option<bool>,
+}
+
+impl Details {
+    /// sysinfo's view of `name`, completed from sysfs on Linux.
+    pub fn of(name: &str, data: Option<&NetworkData>) -> Self {
+        let mut d = sys::details(name);
+        if let Some(data) = data {
+            let mac = data.mac_address();
+            if !mac.is_unspecified() { d.mac = Some(mac.to_string()); }
+            if data.mtu() > 0 { d.mtu = Some(data.mtu()); }
+            let mut nets: Vec<_> = data.ip_networks().to_vec();
+            nets.sort_by_key(|n| n.addr);
+            for n in nets {
+                let cidr = format!("{}/{}", n.addr, n.prefix);
+                match n.addr { IpAddr::V4(_) => d.ipv4.push(cidr), IpAddr::V6(_) => d.ipv6.push(cidr) }
+            }
+        }
+        d
+    }
+
+    /// Administratively up and not known to be down. Unknown counts as up.
+    fn is_up(&self) -> bool {
+        self.admin_up != Some(false)
+            && !matches!(self.operstate.as_deref(), Some("down" | "lowerlayerdown" | "notpresent"))
+    }
+}
+
+// ── Listing ───────────────────────────────────────────────────────────────────
+
+pub fn list(args: &Args) -> Result<()> {
+    let ns = args.namespace();
+    let mut sampler = Sampler::open(ns.as_ref())?;
+    let mut records = output::interface_records(&mut sampler);
+    if let Some(ref pattern) = args.iface {
+        match select::Device::parse(pattern) {
+            Some(dev) => {
+                let name = sampler.find(&dev.map_err(anyhow::Error::msg)?);
+                records.retain(|r| Some(&r.interface) == name.as_ref());
+            }
+            None => {
+                let p = select::Pattern::parse(pattern).map_err(anyhow::Error::msg)?;
+                let alt = canonical_name(pattern).filter(|n| ns.is_none() && n != pattern);
+                records.retain(|r| p.selects(&r.interface) || Some(&r.interface) == alt.as_ref());
+            }
+        }
+    }
+    records.retain(|r| !select::excluded(&r.interface, &args.exclude));
+    if !args.kind.is_empty() { records.retain(|r| args.kind.contains(&r.details.kind)); }
+    if args.up { records.retain(|r| r.details.is_up()); }
+    sort(&mut records, args.sort);
+
+    let format = if args.json { OutputFormat::Jsonl } else { args.output };
+    match format {
+        OutputFormat::Human | OutputFormat::Text => {}
+        OutputFormat::Jsonl => {
+            println!("{}", output::to_json(&records));
+            return Ok(());
+        }
+        OutputFormat::Influx => bail!("influx output is not supported for --list"),
+        OutputFormat::Csv => {
+            let csv = output::CsvFormat::new(args.delimiter);
+            println!("{}", csv.interface_table(&records));
+            return Ok(());
+        }
+    }
+
+    let title = match ns {
+        Some(ref ns) => format!("Available Network Interfaces ({}):", ns),
+        None         => "Available Network Interfaces:".to_string(),
+    };
+    println!("\n{}", styled(&title, C_CYAN, true));
+    println!("{}", "─".repeat(104));
+    println!("{}", styled(&format!("  {:<16} {:<9} {:<9} {:>5}  {:<15} {:<17}  {:>11}  {:>11}",
+        "INTERFACE", "TYPE", "STATE", "MTU", "SPEED", "MAC", "RX", "TX"), C_CYAN, false));
+    for r in &records {
+        let d = &r.details;
+        let speed = match (d.speed_mbps, d.duplex.as_deref()) {
+            (Some(s), Some(dx)) => format!("{} {}", fmt_speed(s), dx),
+            (Some(s), None)     => fmt_speed(s),
+            _                   => "-".to_string(),
+        };
+        println!("  {} {}", styled(&format!("{:<16}", r.interface), C_WHITE, true), styled(&format!(
+            "{:<9} {:<9} {:>5}  {:<15} {:<17}  {:>11}  {:>11}",
+            d.kind.name(), d.operstate.as_deref().unwrap_or("-"),
+            d.mtu.map_or("-".to_string(), |m| m.to_string()), speed,
+            d.mac.as_deref().unwrap_or("-"), fmt_total(r.total_rx), fmt_total(r.total_tx)), C_WHITE, false));
+
+        let mut extra: Vec<String> = d.ipv4.iter().chain(&d.ipv6).cloned().collect();
+        if let Some(ref drv) = d.driver { extra.push(format!("driver {}", drv)); }
+        if let Some(ref m) = d.master   { extra.push(format!("master {}", m)); }
+        if !extra.is_empty() {
+            println!("    {}", styled(&format!("↳ {}", extra.join("  ")), C_GREY, false));
+        }
+    }
+    println!();
+    Ok(())
+}
+
+fn sort(records: &mut [InterfaceRecord], key: SortKey) {
+    // Name first, so every other key breaks ties alphabetically.
+    records.sort_by(|a, b| a.interface.cmp(&b.interface));
+    match key {
+        SortKey::Name  => {}
+        SortKey::Type  => records.sort_by_key(|r| r.details.kind.name()),
+        SortKey::State => records.sort_by_key(|r| !r.details.is_up()),
+        SortKey::Speed => records.sort_by_key(|r| Reverse(r.details.speed_mbps)),
+        SortKey::Rx    => records.sort_by_key(|r| Reverse(r.total_rx)),
+        SortKey::Tx    => records.sort_by_key(|r| Reverse(r.total_tx)),
+        SortKey::Total => records.sort_by_key(|r| Reverse(r.total_rx + r.total_tx)),
+    }
+}
+
+/// Negotiated link speed of `name` in Mb/s, where the platform reports it.
+pub fn speed_mbps(name: &str) -> Option<u64> { sys::speed_mbps(name) }
+
+/// The current name behind `name`: a kernel altname
+/// (`ip link property add dev eth0 altname wan`) gives its interface's name.
+pub fn canonical_name(name: &str) -> Option<String> { sys::canonical_name(name) }
+
+/// A bridge, bond or VLAN: these take the MAC of a port or parent. Only
+/// reads `uevent`, unlike `details`, as it runs on every sample.
+pub fn borrows_mac(name: &str) -> bool {
+    matches!(sys::devtype(name).as_deref(), Some("bridge" | "bond" | "vlan"))
+}
+
+/// `/sys/class/net/*/speed` is in Mb/s.
+pub fn fmt_speed(mbps: u64) -> String {
+    if mbps >= 1000 && mbps % 100 == 0 {
+        format!("{} Gb/s", mbps as f64 / 1000.0)
+    } else {
+        format!("{} Mb/s", mbps)
+    }
+}
+
+// ── Linux ─────────────────────────────────────────────────────────────────────
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::{Details, Kind};
+    use std::path::Path;
+
+    /// ARPHRD_* values of `/sys/class/net/*/type`.
+    const ARPHRD_LOOPBACK: u32 = 772;
+    const ARPHRD_TUNNELS: &[u32] = &[768, 769, 776, 778, 823, 65534];  // ipip, ip6ip6, sit, gre, ip6gre, none (tun, wireguard)
+
+    const IFF_UP: u32 = 0x1;
+
+    pub fn details(name: &str) -> Details {
+        let dir = Path::new("/sys/class/net").join(name);
+        let read = |f: &str| std::fs::read_to_string(dir.join(f)).ok().map(|s| s.trim().to_string());
+        let link = |f: &str| std::fs::read_link(dir.join(f)).ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()));
+
+        let driver = ethtool_driver(name).or_else(|| link("device/driver"));
+        let devtype = devtype(name);
+        let arphrd = read("type").and_then(|t| t.parse::<u32>().ok());
+
+        let kind = kind(&dir, arphrd, devtype.as_deref(), driver.as_deref());
+
+        Details {
+            kind,
+            operstate:  read("operstate"),
+            speed_mbps: speed_mbps(name),
+            duplex:     read("duplex").filter(|d| d != "unknown"),
+            driver,
+            master:     link("master"),
+            admin_up:   read("flags").and_then(|f| u32::from_str_radix(f.trim_start_matches("0x"), 16).ok())
+                            .map(|f| f & IFF_UP != 0),
+            ..Details::default()
+        }
+    }
+
+    /// The interface type from its ARPHRD type, uevent `DEVTYPE`, driver
+    /// and which entries its sysfs directory `dir` has.
+    fn kind(dir: &Path, arphrd: Option<u32>, devtype: Option<&str>, driver: Option<&str>) -> Kind {
+        match (arphrd, devtype) {
+            (Some(ARPHRD_LOOPBACK), _) => Kind::Loopback,
+            (_, Some("bridge")) => Kind::Bridge,
+            (_, Some("bond"))   => Kind::Bond,
+            (_, Some("vlan"))   => Kind::Vlan,
+            (_, Some("wlan"))   => Kind::Wireless,
+            (_, Some("wireguard" | "vxlan" | "geneve" | "ipip" | "gre")) => Kind::Tunnel,
+            _ if dir.join("wireless").exists() || dir.join("phy80211").exists() => Kind::Wireless,
+            _ if dir.join("bridge").exists()   => Kind::Bridge,
+            _ if dir.join("bonding").exists()  => Kind::Bond,
+            _ if dir.join("tun_flags").exists() => Kind::Tunnel,
+            (Some(t), _) if ARPHRD_TUNNELS.contains(&t) => Kind::Tunnel,
+            _ if driver == Some("veth")        => Kind::Veth,
+            _ if dir.join("device").exists()   => Kind::Physical,
+            (Some(_), _) => Kind::Virtual,
+            (None, _)    => Kind::Unknown,
+        }
+    }
+
+    /// `DEVTYPE` from the interface's `uevent`: bridge, bond, vlan, wlan, ...
+    pub fn devtype(name: &str) -> Option<String> {
+        std::fs::read_to_string(Path::new("/sys/class/net").join(name).join("uevent")).ok()?
+            .lines().find_map(|l| l.strip_prefix("DEVTYPE=").map(str::to_string))
+    }
+
+    /// Negotiated speed; reading it fails (EINVAL) on links that are down,
+    /// and virtual links report -1 or nothing.
+    pub fn speed_mbps(name: &str) -> Option<u64> {
+        std::fs::read_to_string(Path::new("/sys/class/net").join(name).join("speed")).ok()
+            .and_then(|s| s.trim().parse::<i64>().ok())
+            .filter(|s| *s > 0)
+            .map(|s| s as u64)
+    }
+
+    /// `if_nametoindex` resolves altnames too (up to IFNAMSIZ - 1 bytes);
+    /// the index then names the interface.
+    pub fn canonical_name(name: &str) -> Option<String> {
+        let c = std::ffi::CString::new(name).ok()?;
+        let index = unsafe { libc::if_nametoindex(c.as_ptr()) };
+        if index == 0 { return None; }
+        let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+        let p = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+        if p.is_null() { return None; }
+        Some(unsafe { std::ffi::CStr::from_ptr(p) }.to_string_lossy().into_owned())
+    }
+
+    /// `ETHTOOL_GDRVINFO`: the driver name, for real and virtual devices.
+    fn ethtool_driver(name: &str) -> Option<String> {
+        const ETHTOOL_GDRVINFO: u32 = 3;
+        // struct ethtool_drvinfo: cmd, then driver[32], then fields we don't need.
+        const DRVINFO_LEN: usize = 196;
+
+        if name.len() >= libc::IFNAMSIZ { return None; }
+        let mut info = [0u8; DRVINFO_LEN];
+        info[..4].copy_from_slice(&ETHTOOL_GDRVINFO.to_ne_bytes());
+        let mut ifr: libc::ifreq = unsafe { std::mem::zeroed() };
+        for (d, s) in ifr.ifr_name.iter_mut().zip(name.bytes()) { *d = s as libc::c_char; }
+        ifr.ifr_ifru.ifru_data = info.as_mut_ptr().cast();
+
+        let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+        if fd < 0 { return None; }
+        let rc = unsafe { libc::ioctl(fd, libc::SIOCETHTOOL as _, &mut ifr) };
+        unsafe { libc::close(fd); }
+        if rc < 0 { return None; }
+        let driver = &info[4..36];
+        let end = driver.iter().position(|b| *b == 0).unwrap_or(driver.len());
+        Some(String::from_utf8_lossy(&driver[..end]).into_owned()).filter(|d| !d.is_empty())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::fs;
+
+        const ETHER: Option<u32> = Some(1);
+
+        /// A fake sysfs directory holding `entries`.
+        fn kind_with(entries: &[&str], arphrd: Option<u32>, devtype: Option<&str>, driver: Option<&str>) -> Kind {
+            let dir = std::env::temp_dir().join(format!("bandwidthmon-test-kind-{}-{}", entries.join("+"), std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            for e in entries { fs::create_dir(dir.join(e)).unwrap(); }
+            let k = kind(&dir, arphrd, devtype, driver);
+            fs::remove_dir_all(&dir).unwrap();
+            k
+        }
+
+        #[test]
+        fn kinds() {
+            assert_eq!(kind_with(&[], Some(ARPHRD_LOOPBACK), None, None), Kind::Loopback);
+            assert_eq!(kind_with(&[], ETHER, Some("bridge"), None), Kind::Bridge);
+            assert_eq!(kind_with(&[], ETHER, Some("bond"), None), Kind::Bond);
+            assert_eq!(kind_with(&["device"], ETHER, Some("vlan"), None), Kind::Vlan);
+            assert_eq!(kind_with(&["device"], ETHER, Some("wlan"), Some("iwlwifi")), Kind::Wireless);
+            assert_eq!(kind_with(&[], Some(65534), Some("wireguard"), None), Kind::Tunnel);
+            assert_eq!(kind_with(&["device", "phy80211"], ETHER, None, Some("iwlwifi")), Kind::Wireless);
+            assert_eq!(kind_with(&["bridge"], ETHER, None, Some("bridge")), Kind::Bridge);
+            assert_eq!(kind_with(&["bonding"], ETHER, None, None), Kind::Bond);
+            assert_eq!(kind_with(&["tun_flags"], ETHER, None, Some("tun")), Kind::Tunnel);
+            assert_eq!(kind_with(&[], Some(776), None, None), Kind::Tunnel);
+            assert_eq!(kind_with(&[], ETHER, None, Some("veth")), Kind::Veth);
+            assert_eq!(kind_with(&["device"], ETHER, None, Some("e1000e")), Kind::Physical);
+            assert_eq!(kind_with(&[], ETHER, None, Some("ifb")), Kind::Virtual);
+            assert_eq!(kind_with(&[], None, None, None), Kind::Unknown);
+        }
+
+        #[test]
+        fn loopback_is_classified() {
+            assert_eq!(details("lo").kind, Kind::Loopback);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use super::Details;
+
+    pub fn details(_name: &str) -> Details { Details::default() }
+
+    pub fn devtype(_name: &str) -> Option<String> { None }
+
+    pub fn speed_mbps(_name: &str) -> Option<u64> { None }
+
+    pub fn canonical_name(_name: &str) -> Option<String> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(interface: &str, kind: Kind, up: bool, speed: Option<u64>, rx: u64, tx: u64) -> InterfaceRecord {
+        InterfaceRecord {
+            interface:  interface.to_string(),
+            details:    Details { kind, speed_mbps: speed, admin_up: Some(up), ..Details::default() },
+            total_rx:   rx,
+            total_tx:   tx,
+            rx_packets: 0,
+            tx_packets: 0,
+            rx_errors:  0,
+            tx_errors:  0,
+        }
+    }
+
+    fn sorted(key: SortKey) -> Vec<String> {
+        let mut records = vec![
+            rec("wlan0", Kind::Wireless, true,  Some(300),  500, 50),
+            rec("eth1",  Kind::Physical, false, None,       0,   0),
+            rec("lo",    Kind::Loopback, true,  None,       900, 900),
+            rec("eth0",  Kind::Physical, true,  Some(1000), 100, 600),
+            rec("br0",   Kind::Bridge,   true,  None,       100, 0),
+        ];
+        sort(&mut records, key);
+        records.into_iter().map(|r| r.interface).collect()
+    }
+
+    #[test]
+    fn sort_orders_with_name_tie_break() {
+        assert_eq!(sorted(SortKey::Name),  ["br0", "eth0", "eth1", "lo", "wlan0"]);
+        assert_eq!(sorted(SortKey::Type),  ["br0", "lo", "eth0", "eth1", "wlan0"]);
+        assert_eq!(sorted(SortKey::State), ["br0", "eth0", "lo", "wlan0", "eth1"]);
+        assert_eq!(sorted(SortKey::Speed), ["eth0", "wlan0", "br0", "eth1", "lo"]);
+        assert_eq!(sorted(SortKey::Rx),    ["lo", "wlan0", "br0", "eth0", "eth1"]);
+        assert_eq!(sorted(SortKey::Tx),    ["lo", "eth0", "wlan0", "br0", "eth1"]);
+        assert_eq!(sorted(SortKey::Total), ["lo", "eth0", "wlan0", "br0", "eth1"]);
+    }
+
+    #[test]
+    fn up_means_not_known_down() {
+        let d = |admin_up, oper: Option<&str>| Details { admin_up, operstate: oper.map(str::to_string), ..Details::default() };
+        assert!(d(None, None).is_up());
+        assert!(d(Some(true), Some("unknown")).is_up());
+        assert!(!d(Some(false), Some("up")).is_up());
+        assert!(!d(Some(true), Some("lowerlayerdown")).is_up());
+    }
+}
@@ -0,0 +1,283 @@
+// File: src/influx.rs
+//! InfluxDB line protocol: the `--output influx` formatter and a batching
+//! HTTP writer for `/write` endpoints (InfluxDB 1.x/2.x compat, VictoriaMetrics,
+//! Telegraf's http_listener).
+//!
+//!   bandwidthmon,host=box1,interface=eth0 rx_bps=1234.5,tx_bps=42,rx_bytes=1234i,... 1760000000000000000
+//!
+//! The writer keeps a bounded in-memory buffer: if the endpoint is down the
+//! oldest lines are dropped once `MAX_BUFFER` is reached, so memory stays
+//! flat no matter how long the outage lasts. Plain `http://` only — put a
+//! local proxy in front for TLS.
+
+use crate::output::SampleRecord;
+use anyhow::{bail, Context, Result};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Lines per POST.
+const BATCH: usize = 5_000;
+
+/// Lines held while the endpoint is unreachable before the oldest go.
+const MAX_BUFFER: usize = 50_000;
+
+/// Longest a line waits before being sent, even if the batch isn't full.
+const FLUSH_EVERY: Duration = Duration::from_secs(10);
+
+const IO_TIMEOUT:  Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+// ── Line protocol ─────────────────────────────────────────────────────────────
+
+/// Measurement names escape commas and spaces.
+fn escape_measurement(s: &str) -> String {
+    s.replace(',', "\\,").replace(' ', "\\ ")
+}
+
+/// Tag keys/values additionally escape `=`.
+fn escape_tag(s: &str) -> String {
+    s.replace('\\', "\\\\").replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ")
+}
+
+/// One sample as a line-protocol line (nanosecond timestamp).
+pub fn line(measurement: &str, host: &str, r: &SampleRecord) -> String {
+    format!(
+        "{},host={},interface={} rx_bps={},tx_bps={},rx_bytes={}i,tx_bytes={}i,total_rx={}i,total_tx={}i,\
+         rx_packets={}i,tx_packets={}i,rx_errors={}i,tx_errors={}i {}",
+        escape_measurement(measurement), escape_tag(host), escape_tag(r.interface),
+        r.rx_bps, r.tx_bps, r.rx_bytes, r.tx_bytes, r.total_rx, r.total_tx,
+        r.rx_packets, r.tx_packets, r.rx_errors, r.tx_errors,
+        r.unix_ns,
+    )
+}
+
+// ── HTTP writer ───────────────────────────────────────────────────────────────
+
+struct Endpoint {
+    host_port: String,
+    path:      String,   // path + query, e.g. "/write?db=net"
+    token:     Option<String>,
+}
+
+impl Endpoint {
+    fn parse(url: &str, token: Option<String>) -> Result<Self> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            bail!("Unsupported Influx URL '{}': only http:// is supported", url);
+        };
+        let (host_port, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None    => (rest, "/write"),
+        };
+        if host_port.is_empty() { bail!("Influx URL '{}' has no host", url); }
+        let host_port = if host_port.contains(':') && !host_port.ends_with(']') {
+            host_port.to_string()
+        } else {
+            format!("{}:80", host_port)
+        };
+        Ok(Self { host_port, path: path.to_string(), token })
+    }
+
+    /// POST one batch. `Ok(true)` = accepted, `Ok(false)` = rejected as bad
+    /// data (4xx — retrying won't help), `Err` = try again later.
+    fn post(&self, body: &str) -> Result<bool> {
+        let addr = self.host_port.to_socket_addrs()?.next().context("Cannot resolve Influx host")?;
+        let mut s = TcpStream::connect_timeout(&addr, IO_TIMEOUT)?;
+        s.set_read_timeout(Some(IO_TIMEOUT))?;
+        s.set_write_timeout(Some(IO_TIMEOUT))?;
+        let auth = self.token.as_ref()
+            .map(|t| format!("Authorization: Token {}\r\n", t))
+            .unwrap_or_default();
+        write!(s, "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+                   Content-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            self.path, self.host_port, body.len(), auth, body)?;
+        s.flush()?;
+
+        let mut status = String::new();
+        BufReader::new(s).read_line(&mut status)?;
+        let code: u16 = status.split_whitespace().nth(1).and_then(|c| c.parse().ok())
+            .context("Malformed HTTP response")?;
+        match code {
+            200..=299             => Ok(true),
+            429 | 500..=599       => bail!("HTTP {}", code),
+            _                     => Ok(false),
+        }
+    }
+}
+
+pub struct InfluxWriter {
+    tx:     Option<SyncSender<Vec<String>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl InfluxWriter {
+    pub fn start(url: &str, token: Option<String>) -> Result<Self> {
+        let ep = Endpoint::parse(url, token)?;
+        let (tx, rx) = sync_channel(64);
+        let worker = std::thread::spawn(move || writer_loop(ep, rx));
+        Ok(Self { tx: Some(tx), worker: Some(worker) })
+    }
+
+    pub fn send(&self, lines: Vec<String>) {
+        if let Some(ref tx) = self.tx { let _ = tx.try_send(lines); }
+    }
+}
+
+impl Drop for InfluxWriter {
+    /// Close the channel and give the worker one last chance to flush.
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(w) = self.worker.take() { let _ = w.join(); }
+    }
+}
+
+/// Append `lines`, dropping the oldest beyond `MAX_BUFFER`.
+fn enqueue(buf: &mut VecDeque<String>, lines: Vec<String>) {
+    buf.extend(lines);
+    let excess = buf.len().saturating_sub(MAX_BUFFER);
+    buf.drain(..excess);
+}
+
+fn writer_loop(ep: Endpoint, rx: Receiver<Vec<String>>) {
+    let mut buf: VecDeque<String> = VecDeque::new();
+    let mut last_flush = Instant::now();
+    let mut backoff = Duration::from_secs(1);
+    let mut retry_at = Instant::now();
+
+    loop {
+        let closed = match rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(lines) => { enqueue(&mut buf, lines); false }
+            Err(RecvTimeoutError::Timeout)      => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        let due = buf.len() >= BATCH || last_flush.elapsed() >= FLUSH_EVERY || closed;
+        if !buf.is_empty() && due && (closed || Instant::now() >= retry_at) {
+            while !buf.is_empty() {
+                let n = buf.len().min(BATCH);
+                let body: String = buf.iter().take(n).map(|l| format!("{}\n", l)).collect();
+                match ep.post(&body) {
+                    Ok(_) => {
+                        // Accepted, or rejected as malformed — either way, done with it.
+                        buf.drain(..n);
+                        backoff = Duration::from_secs(1);
+                    }
+                    Err(_) => {
+                        retry_at = Instant::now() + backoff;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                        break;
+                    }
+                }
+            }
+            last_flush = Instant::now();
+        }
+        if closed { return; }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BandwidthStats;
+    use chrono::{Local, TimeZone};
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    /// Answer `statuses.len()` requests in turn; yields each request's body.
+    fn serve(statuses: Vec<&'static str>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/write?db=net", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            statuses.into_iter().map(|status| {
+                let (s, _) = listener.accept().unwrap();
+                let mut r = BufReader::new(&s);
+                let mut len = 0;
+                loop {
+                    let mut h = String::new();
+                    r.read_line(&mut h).unwrap();
+                    if h == "\r\n" { break; }
+                    if let Some(v) = h.strip_prefix("Content-Length: ") { len = v.trim().parse().unwrap(); }
+                }
+                let mut body = vec![0; len];
+                r.read_exact(&mut body).unwrap();
+                write!(&s, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+                String::from_utf8(body).unwrap()
+            }).collect()
+        });
+        (url, server)
+    }
+
+    fn post(status: &'static str) -> Result<bool> {
+        let (url, server) = serve(vec![status]);
+        let r = Endpoint::parse(&url, None)?.post("m f=1i 1\n");
+        assert_eq!(server.join().unwrap(), ["m f=1i 1\n"]);
+        r
+    }
+
+    #[test]
+    fn tags_escape_commas_equals_spaces_and_backslashes() {
+        assert_eq!(escape_tag(r"a b,c=d\e"), r"a\ b\,c\=d\\e");
+        assert_eq!(escape_measurement("net stats,x=1"), r"net\ stats\,x=1");
+    }
+
+    #[test]
+    fn line_has_tags_integer_fields_and_ns_timestamp() {
+        let ts = Local.timestamp_opt(1_760_000_000, 0).unwrap();
+        let s = BandwidthStats {
+            download_bps: 1234.5, upload_bps: 42.0, rx_delta: 1234, tx_delta: 42,
+            total_rx: 10, total_tx: 20, rx_packets: 3, tx_packets: 4, rx_errors: 5, tx_errors: 6,
+            ..BandwidthStats::default()
+        };
+        let r = SampleRecord::from_stats(&ts, "my eth0", &s);
+        assert_eq!(line("bw", "box,1", &r),
+            "bw,host=box\\,1,interface=my\\ eth0 rx_bps=1234.5,tx_bps=42,rx_bytes=1234i,tx_bytes=42i,\
+             total_rx=10i,total_tx=20i,rx_packets=3i,tx_packets=4i,rx_errors=5i,tx_errors=6i 1760000000000000000");
+    }
+
+    #[test]
+    fn endpoint_defaults() {
+        let ep = Endpoint::parse("http://influx", Some("t".into())).unwrap();
+        assert_eq!((ep.host_port.as_str(), ep.path.as_str()), ("influx:80", "/write"));
+        assert!(Endpoint::parse("https://influx/write", None).is_err());
+        assert!(Endpoint::parse("http:///write", None).is_err());
+    }
+
+    #[test]
+    fn post_status_handling() {
+        assert!(post("204 No Content").unwrap());
+        assert!(!post("400 Bad Request").unwrap());
+        assert!(post("503 Service Unavailable").is_err());
+        assert!(post("429 Too Many Requests").is_err());
+    }
+
+    #[test]
+    fn post_to_closed_port_is_retryable() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        assert!(Endpoint::parse(&format!("http://127.0.0.1:{}", port), None).unwrap().post("m f=1i\n").is_err());
+    }
+
+    #[test]
+    fn writer_flushes_in_batches_on_drop() {
+        let (url, server) = serve(vec!["204 No Content"; 3]);
+        let w = InfluxWriter::start(&url, None).unwrap();
+        w.send((0..12_000).map(|i| format!("m n={}i", i)).collect());
+        drop(w);
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies.iter().map(|b| b.lines().count()).collect::<Vec<_>>(), [5000, 5000, 2000]);
+        assert!(bodies[0].starts_with("m n=0i\nm n=1i\n"));
+        assert!(bodies[2].ends_with("m n=11999i\n"));
+    }
+
+    #[test]
+    fn full_buffer_drops_the_oldest() {
+        let mut buf = VecDeque::new();
+        enqueue(&mut buf, (0..MAX_BUFFER).map(|i| i.to_string()).collect());
+        enqueue(&mut buf, vec!["new".to_string(); 3]);
+        assert_eq!(buf.len(), MAX_BUFFER);
+        assert_eq!(buf.front().unwrap(), "3");
+        assert_eq!(buf.back().unwrap(), "new");
+    }
+}
@@ -0,0 +1,154 @@
+// File: src/link.rs
+//! Link utilisation: rates as a share of the negotiated link speed.
+//!
+//!   bandwidthmon -i eth0 --gauge
+//!   bandwidthmon -i wlan0 --link-speed 300M --gauge --y-max link
+//!
+//! The speed is `/sys/class/net/<if>/speed` on Linux. Wi-Fi, virtual and
+//! namespaced links report none or a meaningless one, so `--link-speed`
+//! overrides it. Link speeds are bits per second with decimal prefixes, the
+//! way NICs are sold; every other rate here is bytes with binary prefixes,
+//! so the conversion happens in this module and nowhere else. `--y-max`
+//! takes either: `800Mbit` and `800Mbps` are decimal like a link speed,
+//! `100MB/s` binary. Both options read their units through
+//! `check::split_unit`, so a lowercase `b` means bits everywhere.
+
+use crate::check::{parse_quantity, split_unit, Counts};
+use crate::{iface, styled, Args, NetworkMonitor, C_GREEN, C_GREY, C_RED, C_YELLOW};
+use std::fmt;
+
+/// Utilisation at which the gauge turns yellow, then red.
+const WARN_PCT: f64 = 70.0;
+const HIGH_PCT: f64 = 90.0;
+
+/// `--link-speed` values in bits per second: `1G`, `2.5Gbit`, `100Mbps`, or a
+/// bare number of Mbit/s as in sysfs.
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let (n, prefix, counts) = split_unit(s).ok_or_else(|| format!("invalid link speed '{}'", s))?;
+    if counts == Some(Counts::Bytes) {
+        return Err(format!("link speeds are in bits, e.g. 1G or 100Mbit (got '{}')", s));
+    }
+    let prefix = match (prefix.as_str(), counts) {
+        // A bare number is Mbit/s as in sysfs; `100bit` is 100 bits.
+        ("", None) => "m",
+        (p, _)     => p,
+    };
+    let mult = match prefix {
+        ""  => 1.0,
+        "k" => 1e3,
+        "m" => 1e6,
+        "g" => 1e9,
+        "t" => 1e12,
+        _   => return Err(format!("unknown unit in link speed '{}' (use K, M, G or T)", s)),
+    };
+    if !(n > 0.0 && n.is_finite()) { return Err("link speed must be greater than 0".to_string()); }
+    Ok(n * mult)
+}
+
+/// Link speed of the monitored interface in bytes per second: the override,
+/// else what the kernel reports. Unknown inside another namespace and when
+/// replaying, where local sysfs would describe some other link.
+pub fn speed(monitor: &NetworkMonitor, args: &Args) -> Option<f64> {
+    if let Some(bits) = args.link_speed { return Some(bits / 8.0); }
+    if monitor.sampler.is_none() || args.namespace().is_some() { return None; }
+    // A group's link is all of its members' together.
+    let mbps: Option<u64> = monitor.members().into_iter().map(iface::speed_mbps).sum();
+    mbps.filter(|&m| m > 0).map(|m| m as f64 * 1e6 / 8.0)
+}
+
+/// `1 Gb/s` for a speed in bytes per second.
+fn fmt_link(bytes_per_sec: f64) -> String {
+    iface::fmt_speed((bytes_per_sec * 8.0 / 1e6).round() as u64)
+}
+
+fn level_color(pct: f64) -> u8 {
+    if pct >= HIGH_PCT { C_RED } else if pct >= WARN_PCT { C_YELLOW } else { C_GREEN }
+}
+
+/// ` 12.3%` after a rate on the speeds line.
+pub fn percent(rate: f64, link: Option<f64>) -> String {
+    match link {
+        Some(l) => {
+            let pct = rate / l * 100.0;
+            format!(" {}", styled(&format!("{:>5.1}%", pct), if pct >= WARN_PCT { level_color(pct) } else { C_GREY }, false))
+        }
+        None => String::new(),
+    }
+}
+
+/// `RX [██████░░░░░░]  42.1%  │  TX [█░░░░░░░░░░░]   3.0%  of 1 Gb/s`, with
+/// bars sharing `cols` columns.
+pub fn gauge_line(rx: f64, tx: f64, link: Option<f64>, cols: usize) -> String {
+    let Some(link) = link else {
+        return styled("Link speed unknown — set it with --link-speed (e.g. 1G)", C_GREY, false);
+    };
+    let width = (cols.saturating_sub(44) / 2).clamp(10, 40);
+    let bar = |label: &str, rate: f64| {
+        let pct = rate / link * 100.0;
+        let filled = ((pct / 100.0 * width as f64).round() as usize).min(width);
+        format!("{} [{}{}] {}", label,
+            styled(&"█".repeat(filled), level_color(pct), false),
+            styled(&"░".repeat(width - filled), C_GREY, false),
+            styled(&format!("{:>5.1}%", pct), level_color(pct), pct >= HIGH_PCT))
+    };
+    format!("{}  │  {}  {}", bar("RX", rx), bar("TX", tx), styled(&format!("of {}", fmt_link(link)), C_GREY, false))
+}
+
+// ── --y-max ───────────────────────────────────────────────────────────────────
+
+/// A fixed top for the charts: the link speed, or a rate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum YMax {
+    Link,
+    Rate(f64),   // bytes per second
+}
+
+impl YMax {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.trim().eq_ignore_ascii_case("link") { return Ok(YMax::Link); }
+        // Bit rates come out decimal like link speeds, byte rates binary.
+        let v = parse_quantity(s)?;
+        if v > 0.0 { Ok(YMax::Rate(v)) } else { Err("--y-max must be greater than 0".to_string()) }
+    }
+
+    /// The chart maximum; `link` falls back to auto-scaling when the speed
+    /// is unknown.
+    pub fn resolve(self, link: Option<f64>) -> Option<f64> {
+        match self { YMax::Link => link, YMax::Rate(v) => Some(v) }
+    }
+}
+
+impl fmt::Display for YMax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self { YMax::Link => f.write_str("link"), YMax::Rate(v) => write!(f, "{}B/s", v) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_speeds_are_decimal_bits() {
+        assert_eq!(parse_speed("1G"), Ok(1e9));
+        assert_eq!(parse_speed("2.5Gbit"), Ok(2.5e9));
+        assert_eq!(parse_speed("100Mbps"), Ok(1e8));
+        assert_eq!(parse_speed("1000"), Ok(1e9));
+        assert_eq!(parse_speed("100bit"), Ok(100.0));
+        assert!(parse_speed("100MB").is_err());
+        assert!(parse_speed("0").is_err());
+    }
+
+    #[test]
+    fn y_max_bits_are_decimal_bytes_binary() {
+        assert_eq!(YMax::parse("link"), Ok(YMax::Link));
+        assert_eq!(YMax::parse("100Mbit"), Ok(YMax::Rate(12.5e6)));
+        assert_eq!(YMax::parse("1Gbit/s"), Ok(YMax::Rate(125e6)));
+        assert_eq!(YMax::parse("100MB/s"), Ok(YMax::Rate(100.0 * 1024.0 * 1024.0)));
+        assert_eq!(YMax::parse("100Mbps"), Ok(YMax::Rate(parse_speed("100Mbps").unwrap() / 8.0)));
+        assert_eq!(YMax::parse("10kb/s"), Ok(YMax::Rate(parse_speed("10kb/s").unwrap() / 8.0)));
+        assert!(YMax::parse("0Mbit").is_err());
+        assert!(YMax::parse("0MB/s").is_err());
+    }
+}
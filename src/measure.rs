@@ -0,0 +1,196 @@
+// File: src/measure.rs
+//! `--once`: sample quietly for `--duration`, print one summary, exit.
+//!
+//! Meant for scripts and CI, so there is no raw mode and no screen control —
+//! stdout receives the summary and nothing else. Ctrl-C ends the measurement
+//! early and still prints what was collected.
+//!
+//!   bandwidthmon -i eth0 --once --duration 30s
+//!   bandwidthmon -i eth0 --once --duration 1m -o jsonl | jq .p95_rx_bps
+//!
+//! `--warn-if` / `--fail-if` / `--nagios` are evaluated on the summary (see
+//! `check`); a failed measurement exits 3 (UNKNOWN) so checks can tell
+//! "couldn't measure" from "threshold crossed".
+
+use crate::check::{self, EXIT_OK, EXIT_UNKNOWN};
+use crate::output::OutputFormat;
+use crate::prometheus::percentile;
+use crate::{fmt_bps, fmt_total, pick_interface, Args, NetworkMonitor, Sinks, INTERVAL};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest we sleep between checks of the stop flag.
+const TICK: Duration = Duration::from_millis(50);
+
+/// `--duration` values: `500ms`, `10s`, `2m`, `1h`, or bare seconds (`1.5`).
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: f64 = num.parse().map_err(|_| format!("invalid duration '{}'", s))?;
+    let secs = match unit.trim() {
+        "ms"                   => n / 1000.0,
+        "" | "s" | "sec"       => n,
+        "m" | "min"            => n * 60.0,
+        "h"                    => n * 3600.0,
+        u => return Err(format!("unknown unit '{}' in duration (use ms, s, m or h)", u)),
+    };
+    if !secs.is_finite() || secs <= 0.0 {
+        return Err("duration must be greater than 0".to_string());
+    }
+    Ok(Duration::from_secs_f64(secs))
+}
+
+// ── Summary ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub interface:     String,
+    pub duration_secs: f64,
+    pub samples:       usize,
+    pub rx_bytes:      u64,
+    pub tx_bytes:      u64,
+    pub avg_rx_bps:    f64,
+    pub avg_tx_bps:    f64,
+    pub peak_rx_bps:   f64,
+    pub peak_tx_bps:   f64,
+    pub p95_rx_bps:    f64,
+    pub p95_tx_bps:    f64,
+}
+
+impl Summary {
+    /// Averages are bytes over measured time rather than the mean of the
+    /// per-interval rates, so a short final interval isn't over-weighted.
+    pub fn from_monitor(monitor: &NetworkMonitor, rx_bytes: u64, tx_bytes: u64, secs: f64) -> Self {
+        let (dl, ul) = (monitor.dl_history(), monitor.ul_history());
+        let per_sec = |b: u64| if secs > 0.0 { b as f64 / secs } else { 0.0 };
+        Self {
+            interface:     monitor.interface.clone(),
+            duration_secs: secs,
+            samples:       monitor.track.sample_count as usize,
+            rx_bytes, tx_bytes,
+            avg_rx_bps:    per_sec(rx_bytes),
+            avg_tx_bps:    per_sec(tx_bytes),
+            peak_rx_bps:   monitor.track.peak_dl,
+            peak_tx_bps:   monitor.track.peak_ul,
+            p95_rx_bps:    percentile(&dl, 0.95),
+            p95_tx_bps:    percentile(&ul, 0.95),
+        }
+    }
+
+    pub fn human(&self) -> String {
+        format!(
+            "Interface: {}   Duration: {:.1}s ({} samples)\n\
+             {:<10} {:>12} {:>12} {:>12} {:>12}\n\
+             {:<10} {:>12} {:>12} {:>12} {:>12}\n\
+             {:<10} {:>12} {:>12} {:>12} {:>12}",
+            self.interface, self.duration_secs, self.samples,
+            "", "Avg", "Peak", "p95", "Bytes",
+            "Download", fmt_bps(self.avg_rx_bps), fmt_bps(self.peak_rx_bps), fmt_bps(self.p95_rx_bps), fmt_total(self.rx_bytes),
+            "Upload",   fmt_bps(self.avg_tx_bps), fmt_bps(self.peak_tx_bps), fmt_bps(self.p95_tx_bps), fmt_total(self.tx_bytes),
+        )
+    }
+}
+
+// ── Entry ─────────────────────────────────────────────────────────────────────
+
+pub fn run(args: &Args) -> Result<()> {
+    let summary = match measure(args) {
+        Ok(s)  => s,
+        Err(e) if args.nagios => { println!("BANDWIDTH UNKNOWN - {:#}", e); std::process::exit(EXIT_UNKNOWN); }
+        Err(e) => { eprintln!("Error: {:#}", e); std::process::exit(EXIT_UNKNOWN); }
+    };
+
+    let verdict = check::evaluate(&summary, &args.warn_if, &args.fail_if);
+    if args.nagios {
+        println!("{}", check::nagios_line(&summary, &verdict, &args.warn_if, &args.fail_if));
+    } else {
+        match args.output {
+            OutputFormat::Jsonl => println!("{}", serde_json::to_string(&summary)?),
+            _                   => println!("{}", summary.human()),
+        }
+        for c in &verdict.critical { eprintln!("CRITICAL: {}", check::describe(c, &summary)); }
+        for c in &verdict.warning  { eprintln!("WARNING: {}",  check::describe(c, &summary)); }
+    }
+    if verdict.code != EXIT_OK { std::process::exit(verdict.code); }
+    Ok(())
+}
+
+fn measure(args: &Args) -> Result<Summary> {
+    if !args.nagios && !matches!(args.output, OutputFormat::Human | OutputFormat::Jsonl | OutputFormat::Text) {
+        bail!("--once prints a summary: use --output human or --output jsonl");
+    }
+    let (target, _) = pick_interface(args)?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || { r.store(false, Ordering::SeqCst); })?;
+
+    let mut sinks = Sinks::open(args, target.name())?;
+    // Enough room that percentiles cover the whole run, not just the tail.
+    let window = (args.duration.as_secs_f64() / INTERVAL.as_secs_f64()).ceil() as usize + 1;
+    let mut monitor = NetworkMonitor::new(target, window.max(args.history), args.namespace().as_ref())?;
+    let _ = monitor.update();  // warm-up, same as the TUI
+    // The warm-up interval isn't part of the run: peak, p95 and sample count
+    // start here, like the byte totals below.
+    monitor.track = Default::default();
+
+    // Short runs still get several samples.
+    let interval = INTERVAL.min(args.duration / 4).max(Duration::from_millis(100));
+    let start = Instant::now();
+    let mut last_update = start;
+    let (mut rx, mut tx, mut secs) = (0u64, 0u64, 0f64);
+
+    let mut take = |monitor: &mut NetworkMonitor| -> Result<()> {
+        let stats = monitor.update()?;
+        sinks.consume(monitor, &stats)?;
+        if stats.elapsed >= 0.001 {
+            rx += stats.rx_delta;
+            tx += stats.tx_delta;
+            secs += stats.elapsed;
+        }
+        Ok(())
+    };
+
+    while running.load(Ordering::SeqCst) && start.elapsed() < args.duration {
+        if last_update.elapsed() >= interval {
+            take(&mut monitor)?;
+            last_update = Instant::now();
+        }
+        std::thread::sleep(TICK.min(args.duration.saturating_sub(start.elapsed())));
+    }
+    // Final partial interval — up to the deadline, or up to Ctrl-C.
+    take(&mut monitor)?;
+
+    Ok(Summary::from_monitor(&monitor, rx, tx, secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("10s"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("10sec"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("2min"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration(" 1.5 "), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("1.5 s"), Ok(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn bad_durations() {
+        assert_eq!(parse_duration("0"), Err("duration must be greater than 0".to_string()));
+        assert!(parse_duration("0ms").is_err());
+        for bad in ["", "s", "ms", "abc", "1.2.3s", "-5s", "10x", "5 days", "1e3"] {
+            assert!(parse_duration(bad).is_err(), "'{}' should not parse", bad);
+        }
+    }
+}
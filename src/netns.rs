@@ -0,0 +1,154 @@
+// File: src/netns.rs
+//! `--pid PID` / `--netns NAME`: watch the interfaces of another network
+//! namespace, e.g. the `eth0` inside a container.
+//!
+//!   bandwidthmon --pid $(docker inspect -f '{{.State.Pid}}' web) -i eth0
+//!   sudo bandwidthmon --netns blue -s
+//!
+//! sysinfo reads `/sys/class/net`, which always shows the namespace sysfs was
+//! mounted in, so namespaced counters come from `net/dev` instead. A PID's
+//! is simply `/proc/<pid>/net/dev`. A named namespace (`ip netns add`) is
+//! entered from a short-lived thread, which opens `/proc/thread-self/net/dev`
+//! there: the open file stays bound to that namespace, so every later read
+//! through it sees the namespace's interfaces. That needs root
+//! (CAP_SYS_ADMIN); `--pid` only needs to be able to see the process.
+//! The routing tables for auto-selection are opened the same way.
+
+use crate::sampler::Totals;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Where named namespaces live (`ip netns`).
+const NETNS_DIR: &str = "/run/netns";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Namespace {
+    Pid(u32),
+    Named(String),
+}
+
+impl Namespace {
+    /// From `--pid` / `--netns`; the PID wins if both came from a config.
+    pub fn from_args(pid: Option<u32>, name: Option<&str>) -> Option<Self> {
+        pid.map(Namespace::Pid).or_else(|| name.map(|n| Namespace::Named(n.to_string())))
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Namespace::Pid(p)   => write!(f, "netns of pid {}", p),
+            Namespace::Named(n) => write!(f, "netns {}", n),
+        }
+    }
+}
+
+/// An open `net/dev` of one namespace, re-read on every sample.
+pub struct NetDev {
+    file: File,
+}
+
+impl NetDev {
+    pub fn open(ns: &Namespace) -> Result<Self> {
+        Ok(Self { file: open_net(ns, "dev")? })
+    }
+
+    /// Counters of every interface in the namespace.
+    pub fn read(&mut self) -> Result<Vec<(String, Totals)>> {
+        let mut text = String::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_string(&mut text).context("Network namespace is gone")?;
+        Ok(parse(&text))
+    }
+}
+
+/// `/proc/net/<file>` as seen from inside `ns`.
+pub fn open_net(ns: &Namespace, file: &str) -> Result<File> {
+    match ns {
+        Namespace::Pid(pid) => File::open(format!("/proc/{}/net/{}", pid, file))
+            .with_context(|| format!("Cannot read the network namespace of pid {} (is it running?)", pid)),
+        Namespace::Named(name) => sys::open_in(&format!("{}/{}", NETNS_DIR, name), file)
+            .with_context(|| format!("Cannot enter network namespace '{}'", name)),
+    }
+}
+
+/// `/proc/net/dev`: two header lines, then `name: rx… tx…` with 8 receive
+/// and 8 transmit columns.
+fn parse(text: &str) -> Vec<(String, Totals)> {
+    text.lines().skip(2).filter_map(|line| {
+        let (name, rest) = line.split_once(':')?;
+        let f: Vec<u64> = rest.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+        if f.len() < 16 { return None; }
+        Some((name.trim().to_string(), Totals {
+            rx: f[0], rx_packets: f[1], rx_errors: f[2],
+            tx: f[8], tx_packets: f[9], tx_errors: f[10],
+        }))
+    }).collect()
+}
+
+// ── Linux ─────────────────────────────────────────────────────────────────────
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use anyhow::{bail, Context, Result};
+    use std::fs::File;
+    use std::os::fd::AsRawFd;
+
+    /// `/proc/net/<file>` as seen from inside the namespace bound at `path`.
+    pub fn open_in(path: &str, file: &str) -> Result<File> {
+        let ns = File::open(path).with_context(|| format!("{} not found (see `ip netns list`)", path))?;
+        // setns moves only the calling thread, so do it on a throwaway one.
+        std::thread::scope(|s| s.spawn(|| -> Result<File> {
+            if unsafe { libc::setns(ns.as_raw_fd(), libc::CLONE_NEWNET) } != 0 {
+                let e = std::io::Error::last_os_error();
+                if e.raw_os_error() == Some(libc::EPERM) { bail!("entering a named namespace needs root"); }
+                bail!("setns: {}", e);
+            }
+            let proc = format!("/proc/thread-self/net/{}", file);
+            File::open(&proc).with_context(|| format!("Cannot open {}", proc))
+        }).join().unwrap_or_else(|_| bail!("namespace thread panicked")))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use anyhow::{bail, Result};
+    use std::fs::File;
+
+    pub fn open_in(_path: &str, _file: &str) -> Result<File> {
+        bail!("Network namespaces are only available on Linux")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NET_DEV: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo: 350685061  148105    0    0    0     0          0         0 350685061  148105    0    0    0     0       0          0
+  eth0: 9876543210 7654321  12    3    0     0          0       456 123456789   98765    4    0    0     0       0          0
+veth1a2b3c4:      42       1    0    0    0     0          0         0        0       0    0    0    0     0       0          0
+ short: 1 2 3
+";
+
+    #[test]
+    fn net_dev_counters() {
+        let rows = parse(NET_DEV);
+        let names: Vec<&str> = rows.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["lo", "eth0", "veth1a2b3c4"]);
+        let t = rows[1].1;
+        assert_eq!((t.rx, t.rx_packets, t.rx_errors), (9_876_543_210, 7_654_321, 12));
+        assert_eq!((t.tx, t.tx_packets, t.tx_errors), (123_456_789, 98_765, 4));
+        assert_eq!(rows[2].1.rx, 42);
+    }
+
+    #[test]
+    fn headers_only() {
+        assert!(parse(&NET_DEV.lines().take(2).collect::<Vec<_>>().join("\n")).is_empty());
+        assert!(parse("").is_empty());
+    }
+}
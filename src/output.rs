@@ -0,0 +1,298 @@
+// File: src/output.rs
+//! Line-oriented sample formats for `--output`.
+//!
+//! Every formatter turns one interval of one interface into exactly one line
+//! so the stream can be piped, `tail -f`'d or appended to a file safely.
+//! `SampleRecord` is the single serialised shape of a sample; formatters and
+//! exporters build from it rather than from `BandwidthStats` directly so the
+//! field names stay identical everywhere.
+
+use crate::export::ExportArgs;
+use crate::influx;
+use crate::iface::Details;
+use crate::sampler::Sampler;
+use crate::textlog;
+use crate::{BandwidthStats, NetworkMonitor};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local, SecondsFormat};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Live charts
+    Human,
+    /// One delimited row per interval, with a header
+    Csv,
+    /// One JSON object per interval (JSON Lines)
+    Jsonl,
+    /// InfluxDB line protocol
+    Influx,
+    /// Plain columns, one line per interval (like `sar -n DEV`); the default
+    /// when stdout is not a terminal
+    Text,
+}
+
+// ── Records ───────────────────────────────────────────────────────────────────
+
+/// One interval of one interface, plus the session statistics so far when
+/// the producer tracks them (the exporters see bare samples).
+#[derive(Debug, Serialize)]
+pub struct SampleRecord<'a> {
+    #[serde(skip)]
+    pub unix_ns:     i64,
+    pub timestamp:   String,
+    pub interface:   &'a str,
+    pub rx_bps:      f64,
+    pub tx_bps:      f64,
+    pub rx_bytes:    u64,
+    pub tx_bytes:    u64,
+    pub total_rx:    u64,
+    pub total_tx:    u64,
+    pub rx_packets:  u64,
+    pub tx_packets:  u64,
+    pub rx_errors:   u64,
+    pub tx_errors:   u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_rx_bps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_tx_bps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_rx_bps:  Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_tx_bps:  Option<f64>,
+}
+
+impl<'a> SampleRecord<'a> {
+    /// A sample of the monitored interface, with session peak / average.
+    pub fn new(ts: &DateTime<Local>, monitor: &'a NetworkMonitor, s: &BandwidthStats) -> Self {
+        Self {
+            peak_rx_bps: Some(monitor.track.peak_dl), peak_tx_bps: Some(monitor.track.peak_ul),
+            avg_rx_bps:  Some(monitor.track.avg_dl),  avg_tx_bps:  Some(monitor.track.avg_ul),
+            ..Self::from_stats(ts, &monitor.interface, s)
+        }
+    }
+
+    /// A bare sample of any interface.
+    pub fn from_stats(ts: &DateTime<Local>, interface: &'a str, s: &BandwidthStats) -> Self {
+        Self {
+            unix_ns:     ts.timestamp_nanos_opt().unwrap_or(0),
+            timestamp:   ts.to_rfc3339_opts(SecondsFormat::Secs, false),
+            interface,
+            rx_bps:      s.download_bps,    tx_bps:      s.upload_bps,
+            rx_bytes:    s.rx_delta,        tx_bytes:    s.tx_delta,
+            total_rx:    s.total_rx,        total_tx:    s.total_tx,
+            rx_packets:  s.rx_packets,      tx_packets:  s.tx_packets,
+            rx_errors:   s.rx_errors,       tx_errors:   s.tx_errors,
+            peak_rx_bps: None,              peak_tx_bps: None,
+            avg_rx_bps:  None,              avg_tx_bps:  None,
+        }
+    }
+}
+
+/// One interface as listed by `--list`: details and cumulative counters.
+#[derive(Debug, Serialize)]
+pub struct InterfaceRecord {
+    pub interface:  String,
+    #[serde(flatten)]
+    pub details:    Details,
+    pub total_rx:   u64,
+    pub total_tx:   u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors:  u64,
+    pub tx_errors:  u64,
+}
+
+pub fn interface_records(sampler: &mut Sampler) -> Vec<InterfaceRecord> {
+    sampler.totals().into_iter()
+        .map(|(name, t)| InterfaceRecord {
+            details:    sampler.details(&name),
+            interface:  name,
+            total_rx:   t.rx,         total_tx:   t.tx,
+            rx_packets: t.rx_packets, tx_packets: t.tx_packets,
+            rx_errors:  t.rx_errors,  tx_errors:  t.tx_errors,
+        })
+        .collect()
+}
+
+// ── Line formats ──────────────────────────────────────────────────────────────
+
+/// The formatter selected by `--output` for the streaming modes.
+pub enum LineFormat {
+    Csv(CsvFormat),
+    Jsonl,
+    Influx { measurement: String, host: String },
+    Text,
+}
+
+impl LineFormat {
+    /// `None` for `--output human`, which is not line-oriented.
+    pub fn new(fmt: OutputFormat, delim: char, export: &ExportArgs) -> Option<Self> {
+        match fmt {
+            OutputFormat::Human  => None,
+            OutputFormat::Csv    => Some(Self::Csv(CsvFormat::new(delim))),
+            OutputFormat::Jsonl  => Some(Self::Jsonl),
+            OutputFormat::Influx => Some(Self::Influx {
+                measurement: export.metric_prefix.clone(),
+                host:        export.host(),
+            }),
+            OutputFormat::Text   => Some(Self::Text),
+        }
+    }
+
+    pub fn header(&self) -> Option<String> {
+        match self {
+            Self::Csv(c) => Some(c.header()),
+            Self::Text   => Some(textlog::header()),
+            Self::Jsonl | Self::Influx { .. } => None,
+        }
+    }
+
+    pub fn line(&self, rec: &SampleRecord) -> String {
+        match self {
+            Self::Csv(c) => c.row(rec),
+            Self::Jsonl  => to_json(rec),
+            Self::Influx { measurement, host } => influx::line(measurement, host, rec),
+            Self::Text   => text_row(rec),
+        }
+    }
+}
+
+// ── Text ──────────────────────────────────────────────────────────────────────
+
+/// The same columns as bandwidthmon2/3 print when piped.
+fn text_row(r: &SampleRecord) -> String {
+    // RFC 3339 "YYYY-MM-DDTHH:MM:SS…" → wall-clock time only.
+    let time = r.timestamp.get(11..19).unwrap_or(&r.timestamp);
+    textlog::row(time, &textlog::Line {
+        interface: r.interface.to_string(),
+        rx_bps:    r.rx_bps,
+        tx_bps:    r.tx_bps,
+        rx_bytes:  r.rx_bytes,
+        tx_bytes:  r.tx_bytes,
+    })
+}
+
+/// Serialise any record as a single JSON line. Our records hold only numbers
+/// and strings, so serialisation cannot fail.
+pub fn to_json<T: Serialize>(v: &T) -> String {
+    serde_json::to_string(v).unwrap_or_default()
+}
+
+/// `,`, `;`, `|`, or `tab` / `\t` for tab-separated output. Only fields
+/// that may hold the delimiter are quoted, so it must be a character that
+/// timestamps and numbers never contain.
+pub fn parse_delimiter(s: &str) -> Result<char> {
+    match s {
+        "tab" | "\\t"           => Ok('\t'),
+        "," | ";" | "|" | "\t"  => Ok(s.as_bytes()[0] as char),
+        _                       => bail!("delimiter must be ',', ';', '|' or 'tab'"),
+    }
+}
+
+// ── CSV ───────────────────────────────────────────────────────────────────────
+
+pub struct CsvFormat {
+    delim: char,
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "timestamp", "interface",
+    "rx_bps", "tx_bps",
+    "rx_bytes", "tx_bytes",
+    "total_rx", "total_tx",
+    "rx_packets", "tx_packets",
+    "rx_errors", "tx_errors",
+];
+
+impl CsvFormat {
+    pub fn new(delim: char) -> Self { Self { delim } }
+
+    pub fn header(&self) -> String {
+        CSV_COLUMNS.join(&self.delim.to_string())
+    }
+
+    pub fn row(&self, r: &SampleRecord) -> String {
+        let fields = [
+            r.timestamp.clone(),
+            self.quote(r.interface),
+            format!("{:.2}", r.rx_bps),  format!("{:.2}", r.tx_bps),
+            r.rx_bytes.to_string(),      r.tx_bytes.to_string(),
+            r.total_rx.to_string(),      r.total_tx.to_string(),
+            r.rx_packets.to_string(),    r.tx_packets.to_string(),
+            r.rx_errors.to_string(),     r.tx_errors.to_string(),
+        ];
+        fields.join(&self.delim.to_string())
+    }
+
+    /// `--list --output csv`.
+    pub fn interface_table(&self, recs: &[InterfaceRecord]) -> String {
+        let d = self.delim.to_string();
+        let mut out = ["interface", "type", "operstate", "mac", "mtu", "speed_mbps", "duplex", "driver", "master",
+            "ipv4", "ipv6", "total_rx", "total_tx", "rx_packets", "tx_packets", "rx_errors", "tx_errors"].join(&d);
+        let opt = |v: &Option<String>| self.quote(v.as_deref().unwrap_or_default());
+        let num = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+        for r in recs {
+            let i = &r.details;
+            out.push('\n');
+            out.push_str(&[
+                self.quote(&r.interface),
+                i.kind.name().to_string(),
+                opt(&i.operstate),        opt(&i.mac),
+                num(i.mtu),               num(i.speed_mbps),
+                opt(&i.duplex),           opt(&i.driver),           opt(&i.master),
+                self.quote(&i.ipv4.join(" ")), self.quote(&i.ipv6.join(" ")),
+                r.total_rx.to_string(),   r.total_tx.to_string(),
+                r.rx_packets.to_string(), r.tx_packets.to_string(),
+                r.rx_errors.to_string(),  r.tx_errors.to_string(),
+            ].join(&d));
+        }
+        out
+    }
+
+    /// RFC 4180 quoting, only when the value would otherwise break the row.
+    fn quote(&self, v: &str) -> String {
+        if v.contains(self.delim) || v.contains(['"', '\n', '\r']) {
+            format!("\"{}\"", v.replace('"', "\"\""))
+        } else {
+            v.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn delimiters() {
+        assert_eq!(parse_delimiter(",").unwrap(), ',');
+        assert_eq!(parse_delimiter("|").unwrap(), '|');
+        assert_eq!(parse_delimiter("tab").unwrap(), '\t');
+        assert_eq!(parse_delimiter("\\t").unwrap(), '\t');
+        for bad in [":", "-", "+", ".", "\"", "", ",,", "0"] {
+            assert!(parse_delimiter(bad).is_err(), "'{}' should be refused", bad);
+        }
+    }
+
+    #[test]
+    fn rows_quote_only_what_needs_it() {
+        let ts = Local.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+        let s = BandwidthStats { download_bps: 1.5, ..BandwidthStats::default() };
+        for delim in [',', ';', '|', '\t'] {
+            let csv = CsvFormat::new(delim);
+            let row = csv.row(&SampleRecord::from_stats(&ts, "eth0", &s));
+            assert_eq!(row.split(delim).count(), CSV_COLUMNS.len());
+            assert!(!row.contains('"'));
+            assert_eq!(row.split(delim).nth(2), Some("1.50"));
+        }
+        let csv = CsvFormat::new(';');
+        assert_eq!(csv.quote("a;b"), "\"a;b\"");
+        assert_eq!(csv.quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv.quote("a\rb"), "\"a\rb\"");
+        assert_eq!(csv.quote("a,b"), "a,b");
+    }
+}
@@ -0,0 +1,171 @@
+// File: src/packet.rs
+//! Just enough IPv4 / IPv6 parsing for the capture views: addresses,
+//! transport protocol and ports. Bytes are attributed by the captured
+//! length, so nothing beyond the first transport header is looked at.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub const PROTO_ICMP:   u8 = 1;
+pub const PROTO_TCP:    u8 = 6;
+pub const PROTO_UDP:    u8 = 17;
+pub const PROTO_ICMPV6: u8 = 58;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Packet {
+    pub proto: u8,
+    pub src:   IpAddr,
+    pub dst:   IpAddr,
+    pub sport: u16,   // 0 when there is no port (ICMP, later fragments)
+    pub dport: u16,
+    pub len:   usize, // bytes on the wire, for accounting
+}
+
+pub fn proto_name(proto: u8) -> String {
+    match proto {
+        PROTO_TCP    => "tcp".to_string(),
+        PROTO_UDP    => "udp".to_string(),
+        PROTO_ICMP   => "icmp".to_string(),
+        PROTO_ICMPV6 => "icmp6".to_string(),
+        p            => p.to_string(),
+    }
+}
+
+/// An IP packet starting at `data[0]`; `len` is what it counts for.
+pub fn parse_ip(data: &[u8], len: usize) -> Option<Packet> {
+    match data.first()? >> 4 {
+        4 => {
+            if data.len() < 20 { return None; }
+            let ihl = (data[0] & 0x0f) as usize * 4;
+            if ihl < 20 { return None; }
+            let later_fragment = u16::from_be_bytes([data[6], data[7]]) & 0x1fff != 0;
+            let src = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+            let dst = Ipv4Addr::new(data[16], data[17], data[18], data[19]);
+            let l4 = if later_fragment { &[][..] } else { data.get(ihl..).unwrap_or_default() };
+            Some(with_ports(data[9], src.into(), dst.into(), l4, len))
+        }
+        6 => {
+            if data.len() < 40 { return None; }
+            let src: [u8; 16] = data[8..24].try_into().ok()?;
+            let dst: [u8; 16] = data[24..40].try_into().ok()?;
+            // Walk extension headers to the transport header.
+            let (mut next, mut off) = (data[6], 40);
+            loop {
+                match next {
+                    0 | 43 | 60 => {
+                        let h = data.get(off..off + 2)?;
+                        next = h[0];
+                        off += (h[1] as usize + 1) * 8;
+                    }
+                    44 => {
+                        let h = data.get(off..off + 4)?;
+                        next = h[0];
+                        if u16::from_be_bytes([h[2], h[3]]) & 0xfff8 != 0 { off = data.len(); break; }
+                        off += 8;
+                    }
+                    _ => break,
+                }
+            }
+            let l4 = data.get(off..).unwrap_or_default();
+            Some(with_ports(next, Ipv6Addr::from(src).into(), Ipv6Addr::from(dst).into(), l4, len))
+        }
+        _ => None,
+    }
+}
+
+fn with_ports(proto: u8, src: IpAddr, dst: IpAddr, l4: &[u8], len: usize) -> Packet {
+    let (sport, dport) = match (proto, l4.get(..4)) {
+        (PROTO_TCP | PROTO_UDP, Some(p)) => (u16::from_be_bytes([p[0], p[1]]), u16::from_be_bytes([p[2], p[3]])),
+        _ => (0, 0),
+    };
+    Packet { proto, src, dst, sport, dport, len }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// IPv4 header (20 bytes + `opts`) then `l4`.
+    fn v4(proto: u8, frag: u16, opts: &[u8], l4: &[u8]) -> Vec<u8> {
+        let mut h = vec![0x40 | (5 + opts.len() / 4) as u8, 0, 0, 0, 0, 0];
+        h.extend(frag.to_be_bytes());
+        h.extend([64, proto, 0, 0, 10, 0, 0, 1, 192, 168, 1, 2]);
+        h.extend(opts);
+        h.extend(l4);
+        h
+    }
+
+    /// IPv6 header from 2001:db8::1 to 2001:db8::2, `next` header first.
+    fn v6(next: u8, rest: &[u8]) -> Vec<u8> {
+        let mut h = vec![0x60, 0, 0, 0, 0, 0, next, 64];
+        h.extend(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).octets());
+        h.extend(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2).octets());
+        h.extend(rest);
+        h
+    }
+
+    const PORTS: [u8; 4] = [0x01, 0xbb, 0xc3, 0x50];   // 443 → 50000
+
+    #[test]
+    fn ipv4_addresses_and_ports() {
+        let p = parse_ip(&v4(PROTO_TCP, 0, &[], &PORTS), 1500).unwrap();
+        assert_eq!(p.src, "10.0.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(p.dst, "192.168.1.2".parse::<IpAddr>().unwrap());
+        assert_eq!((p.proto, p.sport, p.dport, p.len), (PROTO_TCP, 443, 50000, 1500));
+    }
+
+    #[test]
+    fn ipv4_options_are_skipped() {
+        let p = parse_ip(&v4(PROTO_UDP, 0, &[1, 1, 1, 1], &PORTS), 60).unwrap();
+        assert_eq!((p.sport, p.dport), (443, 50000));
+    }
+
+    #[test]
+    fn ipv4_later_fragment_has_no_ports() {
+        let p = parse_ip(&v4(PROTO_UDP, 185, &[], &PORTS), 60).unwrap();
+        assert_eq!((p.proto, p.sport, p.dport), (PROTO_UDP, 0, 0));
+    }
+
+    #[test]
+    fn icmp_and_short_transport_have_no_ports() {
+        assert_eq!(parse_ip(&v4(PROTO_ICMP, 0, &[], &PORTS), 60).unwrap().sport, 0);
+        assert_eq!(parse_ip(&v4(PROTO_TCP, 0, &[], &PORTS[..3]), 60).unwrap().dport, 0);
+    }
+
+    #[test]
+    fn ipv6_addresses_and_ports() {
+        let p = parse_ip(&v6(PROTO_UDP, &PORTS), 100).unwrap();
+        assert_eq!(p.src, "2001:db8::1".parse::<IpAddr>().unwrap());
+        assert_eq!(p.dst, "2001:db8::2".parse::<IpAddr>().unwrap());
+        assert_eq!((p.proto, p.sport, p.dport), (PROTO_UDP, 443, 50000));
+    }
+
+    #[test]
+    fn ipv6_extension_headers_are_walked() {
+        // Hop-by-hop (8 bytes) then a first fragment (offset 0).
+        let mut rest = vec![44, 0, 0, 0, 0, 0, 0, 0];
+        rest.extend([PROTO_TCP, 0, 0, 0, 0, 0, 0, 1]);
+        rest.extend(PORTS);
+        let p = parse_ip(&v6(0, &rest), 100).unwrap();
+        assert_eq!((p.proto, p.sport, p.dport), (PROTO_TCP, 443, 50000));
+
+        // A later fragment carries no transport header.
+        let p = parse_ip(&v6(44, &[PROTO_TCP, 0, 0x05, 0xc8, 0, 0, 0, 1, 1, 2, 3, 4]), 100).unwrap();
+        assert_eq!((p.proto, p.sport, p.dport), (PROTO_TCP, 0, 0));
+    }
+
+    #[test]
+    fn truncated_and_foreign_packets_are_rejected() {
+        assert!(parse_ip(&[], 0).is_none());
+        assert!(parse_ip(&v4(PROTO_TCP, 0, &[], &[])[..19], 19).is_none());
+        assert!(parse_ip(&v6(PROTO_TCP, &[])[..39], 39).is_none());
+        assert!(parse_ip(&[0x50; 40], 40).is_none());
+        // An IPv4 header length below the 20-byte minimum.
+        for ihl in 0..5 {
+            let mut p = v4(PROTO_TCP, 0, &[], &PORTS);
+            p[0] = 0x40 | ihl;
+            assert!(parse_ip(&p, 60).is_none(), "IHL {} should be rejected", ihl);
+        }
+        // An extension header cut short.
+        assert!(parse_ip(&v6(0, &[PROTO_TCP]), 41).is_none());
+    }
+}
@@ -0,0 +1,788 @@
+// File: src/pcap.rs
+//! `bandwidthmon pcap FILE` — bandwidth over time from a capture file.
+//!
+//!   bandwidthmon pcap customer.pcapng
+//!   bandwidthmon pcap dump.pcap --local 10.1.2.3,fd00::/64 --interval 100ms
+//!   bandwidthmon pcap dump.pcap --filter "tcp port 443" -o csv > rates.csv
+//!
+//! Reads classic pcap (µs or ns timestamps, either byte order) and pcapng.
+//! Frame lengths as they were on the wire, like the interface counters, are
+//! summed into `--interval` buckets by timestamp; each bucket becomes one
+//! sample fed through the same `NetworkMonitor`, summary and chart renderer
+//! as the live view. Out-of-order packets land in the right bucket, and
+//! stretches without traffic are zero samples.
+//!
+//! Direction comes from `--local` when given: packets sent from one of those
+//! addresses are upload, everything else download. Without it, pcapng
+//! direction flags and Linux "cooked" headers are used where the capture
+//! recorded them; otherwise all traffic counts as download.
+
+use crate::filter::{Filter, Net};
+use crate::measure::{parse_duration, Summary};
+use crate::output::{LineFormat, OutputFormat, SampleRecord};
+use crate::packet;
+use crate::{print_chart, styled, Args, BandwidthStats, NetworkMonitor, C_CYAN, C_GREY, C_YELLOW};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, TimeZone};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{stdout, BufReader, ErrorKind, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// More buckets than this means a bad timestamp or far too small an interval.
+const MAX_BUCKETS: i64 = 10_000_000;
+
+/// Larger records or blocks mean a corrupt file, not a big packet.
+const MAX_RECORD: usize = 16 << 20;
+
+/// Shorter buckets fall below the monitor's 1 ms sample guard and would all
+/// be dropped.
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+// ── CLI ───────────────────────────────────────────────────────────────────────
+
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let d = parse_duration(s)?;
+    if d < MIN_INTERVAL { return Err(format!("--interval must be at least {:?}", MIN_INTERVAL)); }
+    Ok(d)
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PcapArgs {
+    /// Capture file (pcap or pcapng)
+    pub file: PathBuf,
+
+    /// Bucket width: 100ms, 1s, 1m, …
+    #[arg(long, default_value = "1s", value_parser = parse_interval)]
+    pub interval: Duration,
+
+    /// Addresses or networks of the captured host, comma-separated: packets
+    /// from them count as upload, the rest as download
+    #[arg(long, value_name = "ADDR[/LEN]", value_delimiter = ',', value_parser = Net::parse)]
+    pub local: Vec<Net>,
+
+    /// Only count packets matching EXPR (same syntax as with --capture)
+    #[arg(long, value_name = "EXPR", value_parser = Filter::parse)]
+    pub filter: Option<Filter>,
+
+    /// human: summary and charts; csv / jsonl / text / influx: one line per interval
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Human)]
+    pub output: OutputFormat,
+}
+
+// ── File formats ──────────────────────────────────────────────────────────────
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Format { Pcap, PcapNg }
+
+/// One captured frame, link header included.
+struct Frame<'a> {
+    ts_ns:    i64,
+    link:     u32,            // LINKTYPE_* of the capturing interface
+    data:     &'a [u8],       // captured bytes, possibly truncated
+    len:      u64,            // length on the wire
+    outgoing: Option<bool>,   // pcapng direction flag, when recorded
+}
+
+/// Read every frame of `path`, in file order.
+fn read_file(path: &Path, mut each: impl FnMut(&Frame)) -> Result<Format> {
+    let file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+    let mut r = BufReader::with_capacity(1 << 16, file);
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic).with_context(|| format!("{} is too short to be a capture file", path.display()))?;
+    let res = match magic {
+        [0x0a, 0x0d, 0x0d, 0x0a] => read_pcapng(&mut r, &mut each).map(|_| Format::PcapNg),
+        m => read_pcap(&mut r, m, &mut each).map(|_| Format::Pcap),
+    };
+    match res {
+        // Captures cut off mid-write are common; keep what was complete.
+        Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == ErrorKind::UnexpectedEof) => {
+            eprintln!("Warning: {} ends in the middle of a packet; the rest is ignored", path.display());
+            Ok(if magic == [0x0a, 0x0d, 0x0d, 0x0a] { Format::PcapNg } else { Format::Pcap })
+        }
+        r => r.with_context(|| format!("Cannot read {}", path.display())),
+    }
+}
+
+/// Integers in the byte order of the file (or pcapng section).
+#[derive(Copy, Clone)]
+struct Order { le: bool }
+
+impl Order {
+    fn u16(self, b: &[u8], at: usize) -> u16 {
+        let v = [b[at], b[at + 1]];
+        if self.le { u16::from_le_bytes(v) } else { u16::from_be_bytes(v) }
+    }
+
+    fn u32(self, b: &[u8], at: usize) -> u32 {
+        let v = [b[at], b[at + 1], b[at + 2], b[at + 3]];
+        if self.le { u32::from_le_bytes(v) } else { u32::from_be_bytes(v) }
+    }
+}
+
+/// Fill `buf` completely; false on a clean end of file before the first byte.
+fn read_or_eof(r: &mut impl Read, buf: &mut [u8]) -> Result<bool> {
+    let mut got = 0;
+    while got < buf.len() {
+        match r.read(&mut buf[got..]) {
+            Ok(0) if got == 0 => return Ok(false),
+            Ok(0)  => return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into()),
+            Ok(n)  => got += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(true)
+}
+
+fn read_pcap(r: &mut impl Read, magic: [u8; 4], each: &mut impl FnMut(&Frame)) -> Result<()> {
+    let (order, nanos) = match magic {
+        [0xd4, 0xc3, 0xb2, 0xa1] => (Order { le: true },  false),
+        [0xa1, 0xb2, 0xc3, 0xd4] => (Order { le: false }, false),
+        [0x4d, 0x3c, 0xb2, 0xa1] => (Order { le: true },  true),
+        [0xa1, 0xb2, 0x3c, 0x4d] => (Order { le: false }, true),
+        _ => bail!("not a pcap or pcapng file"),
+    };
+    let mut header = [0u8; 20];
+    r.read_exact(&mut header)?;
+    // The top bits may carry FCS information.
+    let link = order.u32(&header, 16) & 0x0fff_ffff;
+
+    let mut rec = [0u8; 16];
+    let mut buf = Vec::new();
+    while read_or_eof(r, &mut rec)? {
+        let (sec, frac) = (order.u32(&rec, 0) as i64, order.u32(&rec, 4) as i64);
+        let (caplen, len) = (order.u32(&rec, 8) as usize, order.u32(&rec, 12) as u64);
+        if caplen > MAX_RECORD { bail!("corrupt record ({} bytes)", caplen); }
+        buf.resize(caplen, 0);
+        r.read_exact(&mut buf)?;
+        let ts_ns = sec * 1_000_000_000 + if nanos { frac } else { frac * 1000 };
+        each(&Frame { ts_ns, link, data: &buf, len, outgoing: None });
+    }
+    Ok(())
+}
+
+/// What an Interface Description Block says about its packets.
+struct Interface {
+    link:      u32,
+    snaplen:   usize,
+    tsresol:   u8,    // if_tsresol: 10^-n, or 2^-n with the top bit set
+    tsoffset:  i64,   // if_tsoffset, seconds
+}
+
+impl Interface {
+    fn ts_ns(&self, units: u64) -> i64 {
+        let units = units as i128;
+        let ns = if self.tsresol & 0x80 != 0 {
+            (units * 1_000_000_000) >> (self.tsresol & 0x7f).min(127)
+        } else if self.tsresol <= 9 {
+            units * 10i128.pow(9 - self.tsresol as u32)
+        } else {
+            units / 10i128.pow((self.tsresol as u32 - 9).min(30))
+        };
+        (ns + self.tsoffset as i128 * 1_000_000_000) as i64
+    }
+}
+
+/// `(code, value)` of each option in `b`, up to `opt_endofopt`.
+fn options(b: &[u8], order: Order) -> Vec<(u16, &[u8])> {
+    let mut out = Vec::new();
+    let mut at = 0;
+    while at + 4 <= b.len() {
+        let (code, len) = (order.u16(b, at), order.u16(b, at + 2) as usize);
+        if code == 0 { break; }
+        let Some(value) = b.get(at + 4..at + 4 + len) else { break };
+        out.push((code, value));
+        at += 4 + len.next_multiple_of(4);
+    }
+    out
+}
+
+/// Direction bits of `epb_flags`: 1 inbound, 2 outbound.
+fn direction(opts: &[(u16, &[u8])], order: Order) -> Option<bool> {
+    let (_, v) = opts.iter().find(|(code, v)| *code == 2 && v.len() == 4)?;
+    match order.u32(v, 0) & 3 { 1 => Some(false), 2 => Some(true), _ => None }
+}
+
+fn read_pcapng(r: &mut impl Read, each: &mut impl FnMut(&Frame)) -> Result<()> {
+    let mut order = Order { le: true };
+    let mut ifaces: Vec<Interface> = Vec::new();
+    let mut last_ts = 0i64;
+    let mut head = [0u8; 8];
+    let mut body = Vec::new();
+    // The magic already read was the first block's type.
+    let mut first = true;
+
+    loop {
+        if first {
+            head[..4].copy_from_slice(&[0x0a, 0x0d, 0x0d, 0x0a]);
+            r.read_exact(&mut head[4..])?;
+            first = false;
+        } else if !read_or_eof(r, &mut head)? {
+            break;
+        }
+        let kind = order.u32(&head, 0);
+        let total = if kind == 0x0a0d0d0a {
+            // Section header: the byte-order magic decides how to read the rest.
+            let mut bom = [0u8; 4];
+            r.read_exact(&mut bom)?;
+            order = match bom {
+                [0x4d, 0x3c, 0x2b, 0x1a] => Order { le: true },
+                [0x1a, 0x2b, 0x3c, 0x4d] => Order { le: false },
+                _ => bail!("not a pcapng file (bad byte-order magic)"),
+            };
+            ifaces.clear();
+            order.u32(&head, 4) as usize
+        } else {
+            order.u32(&head, 4) as usize
+        };
+        if total < 12 || total % 4 != 0 || total > MAX_RECORD { bail!("corrupt pcapng block ({} bytes)", total); }
+        // Body plus the trailing length; the section header's magic is read already.
+        let rest = if kind == 0x0a0d0d0a { total - 12 } else { total - 8 };
+        if rest < 4 { bail!("corrupt pcapng block ({} bytes)", total); }
+        body.resize(rest, 0);
+        r.read_exact(&mut body)?;
+        let b = &body[..rest - 4];
+
+        match kind {
+            // Interface Description Block
+            1 if b.len() >= 8 => {
+                let mut iface = Interface {
+                    link: order.u16(b, 0) as u32, snaplen: order.u32(b, 4) as usize, tsresol: 6, tsoffset: 0,
+                };
+                for (code, v) in options(&b[8..], order) {
+                    match (code, v.len()) {
+                        (9, 1)  => iface.tsresol = v[0],
+                        (14, 8) => iface.tsoffset = if order.le { i64::from_le_bytes(v.try_into()?) } else { i64::from_be_bytes(v.try_into()?) },
+                        _ => {}
+                    }
+                }
+                ifaces.push(iface);
+            }
+            // Enhanced Packet Block, and the obsolete Packet Block
+            6 | 2 if b.len() >= 20 => {
+                let id = if kind == 6 { order.u32(b, 0) as usize } else { order.u16(b, 0) as usize };
+                let iface = ifaces.get(id).with_context(|| format!("packet for undeclared interface {}", id))?;
+                let units = (order.u32(b, 4) as u64) << 32 | order.u32(b, 8) as u64;
+                let (caplen, len) = (order.u32(b, 12) as usize, order.u32(b, 16) as u64);
+                let Some(data) = b.get(20..20 + caplen) else { bail!("corrupt packet block") };
+                let opts = options(b.get(20 + caplen.next_multiple_of(4)..).unwrap_or_default(), order);
+                last_ts = iface.ts_ns(units);
+                each(&Frame { ts_ns: last_ts, link: iface.link, data, len, outgoing: direction(&opts, order) });
+            }
+            // Simple Packet Block: no timestamp, so it shares the previous one.
+            3 if b.len() >= 4 => {
+                let iface = ifaces.first().context("packet before any interface description")?;
+                let len = order.u32(b, 0) as u64;
+                let caplen = (len as usize).min(b.len() - 4).min(if iface.snaplen > 0 { iface.snaplen } else { usize::MAX });
+                each(&Frame { ts_ns: last_ts, link: iface.link, data: &b[4..4 + caplen], len, outgoing: None });
+            }
+            _ => {}   // name resolution, statistics, custom blocks, …
+        }
+    }
+    Ok(())
+}
+
+// ── Link layers ───────────────────────────────────────────────────────────────
+
+const LINKTYPE_NULL:        u32 = 0;
+const LINKTYPE_ETHERNET:    u32 = 1;
+const LINKTYPE_RAW:         u32 = 101;
+const LINKTYPE_LOOP:        u32 = 108;
+const LINKTYPE_LINUX_SLL:   u32 = 113;
+const LINKTYPE_IPV4:        u32 = 228;
+const LINKTYPE_IPV6:        u32 = 229;
+const LINKTYPE_LINUX_SLL2:  u32 = 276;
+
+/// `sll_pkttype` of packets the capturing host sent.
+const PACKET_OUTGOING: u8 = 4;
+
+/// The IP header inside a frame, and whether the link header says the
+/// capturing host sent it. `None` for non-IP frames and unknown link types.
+fn network_layer(link: u32, d: &[u8]) -> (Option<&[u8]>, Option<bool>) {
+    let be16 = |at: usize| d.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    match link {
+        LINKTYPE_ETHERNET => {
+            let mut at = 12;
+            // Skip 802.1Q / 802.1ad tags.
+            while matches!(be16(at), Some(0x8100 | 0x88a8 | 0x9100)) { at += 4; }
+            match be16(at) {
+                Some(0x0800 | 0x86dd) => (d.get(at + 2..), None),
+                _ => (None, None),
+            }
+        }
+        // A 4-byte address family; parse_ip checks the IP version itself.
+        LINKTYPE_NULL | LINKTYPE_LOOP => (d.get(4..), None),
+        // 12 and 14 are raw IP on some BSDs.
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 | 12 | 14 => (Some(d), None),
+        LINKTYPE_LINUX_SLL => match be16(14) {
+            Some(0x0800 | 0x86dd) => (d.get(16..), be16(0).map(|t| t == PACKET_OUTGOING as u16)),
+            _ => (None, None),
+        },
+        LINKTYPE_LINUX_SLL2 => match be16(0) {
+            Some(0x0800 | 0x86dd) => (d.get(20..), d.get(10).map(|t| *t == PACKET_OUTGOING)),
+            _ => (None, None),
+        },
+        _ => (None, None),
+    }
+}
+
+// ── Bucketing ─────────────────────────────────────────────────────────────────
+
+#[derive(Copy, Clone, Default)]
+struct Bucket {
+    rx:         u64,
+    tx:         u64,
+    rx_packets: u64,
+    tx_packets: u64,
+}
+
+/// Where the download / upload split came from, for the header line.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Split { Local, Capture, None }
+
+struct Analysis {
+    format:  Format,
+    buckets: BTreeMap<i64, Bucket>,   // keyed by timestamp / interval
+    packets: u64,                     // every frame in the file
+    counted: u64,                     // frames that passed --filter
+    non_ip:  u64,                     // frames --local / --filter could not look into
+    split:   Split,
+}
+
+fn analyse(p: &PcapArgs) -> Result<Analysis> {
+    let width = p.interval.as_nanos().max(1) as i64;
+    let mut buckets: BTreeMap<i64, Bucket> = BTreeMap::new();
+    let (mut packets, mut counted, mut non_ip) = (0u64, 0u64, 0u64);
+    let mut directed = false;
+
+    let format = read_file(&p.file, |f| {
+        packets += 1;
+        let (ip, link_dir) = network_layer(f.link, f.data);
+        let parsed = ip.and_then(|ip| packet::parse_ip(ip, f.len as usize));
+        if parsed.is_none() && (p.filter.is_some() || !p.local.is_empty()) { non_ip += 1; }
+        if let Some(filter) = &p.filter {
+            if !parsed.is_some_and(|pk| filter.matches(&pk)) { return; }
+        }
+        let outgoing = if p.local.is_empty() {
+            f.outgoing.or(link_dir).inspect(|_| directed = true).unwrap_or(false)
+        } else {
+            parsed.is_some_and(|pk| p.local.iter().any(|n| n.contains(pk.src)))
+        };
+        counted += 1;
+        let b = buckets.entry(f.ts_ns.div_euclid(width)).or_default();
+        if outgoing { b.tx += f.len; b.tx_packets += 1; } else { b.rx += f.len; b.rx_packets += 1; }
+    })?;
+
+    let split = if !p.local.is_empty() { Split::Local } else if directed { Split::Capture } else { Split::None };
+    Ok(Analysis { format, buckets, packets, counted, non_ip, split })
+}
+
+// ── Entry ─────────────────────────────────────────────────────────────────────
+
+pub fn run(p: &PcapArgs, args: &Args) -> Result<()> {
+    let a = analyse(p)?;
+    let width = p.interval.as_nanos().max(1) as i64;
+    let (first, last) = match (a.buckets.keys().next(), a.buckets.keys().next_back()) {
+        (Some(f), Some(l)) => (*f, *l),
+        _ => {
+            println!("{}: no packets{}", p.file.display(),
+                if a.packets > 0 { format!(" matched the filter (of {})", a.packets) } else { String::new() });
+            return Ok(());
+        }
+    };
+    let n = last - first + 1;
+    if n > MAX_BUCKETS {
+        bail!("The capture spans {} intervals of {:?}; use a larger --interval", n, p.interval);
+    }
+
+    let name = p.file.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut monitor = NetworkMonitor::detached(name, n as usize);
+    let secs = p.interval.as_secs_f64();
+    let lines = LineFormat::new(p.output, args.delimiter, &args.export);
+    let mut out = stdout().lock();
+    if let Some(h) = lines.as_ref().and_then(LineFormat::header) { writeln!(out, "{}", h)?; }
+
+    let mut stats = BandwidthStats {
+        download_bps: 0.0, upload_bps: 0.0, total_rx: 0, total_tx: 0, rx_delta: 0, tx_delta: 0,
+        elapsed: secs, rx_packets: 0, tx_packets: 0, rx_errors: 0, tx_errors: 0,
+    };
+    for i in first..=last {
+        let b = a.buckets.get(&i).copied().unwrap_or_default();
+        stats.rx_delta     = b.rx;
+        stats.tx_delta     = b.tx;
+        stats.download_bps = b.rx as f64 / secs;
+        stats.upload_bps   = b.tx as f64 / secs;
+        stats.total_rx    += b.rx;
+        stats.total_tx    += b.tx;
+        stats.rx_packets  += b.rx_packets;
+        stats.tx_packets  += b.tx_packets;
+        let ts = time_of(i * width);
+        monitor.push(&stats, ts.timestamp_millis());
+        if let Some(ref fmt) = lines {
+            match writeln!(out, "{}", fmt.line(&SampleRecord::new(&ts, &monitor, &stats))) {
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(()),
+                r => r?,
+            }
+        }
+    }
+    if lines.is_some() { return Ok(()); }
+    drop(out);
+
+    // ── Human: what was read, the summary, then the charts ──────────────────
+    let format = match a.format { Format::Pcap => "pcap", Format::PcapNg => "pcapng" };
+    println!("{} {}", styled(&p.file.display().to_string(), C_CYAN, true), styled(&format!(
+        "({}, {} packets, {} → {}, {:?} intervals)", format, a.packets,
+        time_of(first * width).format("%Y-%m-%d %H:%M:%S"), time_of((last + 1) * width).format("%Y-%m-%d %H:%M:%S"),
+        p.interval), C_GREY, false));
+    if let Some(ref f) = p.filter {
+        println!("Filter:    {} ({} of {} packets)", f, a.counted, a.packets);
+    }
+    match a.split {
+        Split::Local   => println!("Direction: from --local {}",
+            p.local.iter().map(Net::to_string).collect::<Vec<_>>().join(", ")),
+        Split::Capture => println!("Direction: as recorded in the capture"),
+        Split::None    => println!("{}", styled(
+            "Direction: not recorded in the capture, all traffic counts as download (see --local)", C_YELLOW, false)),
+    }
+    if a.non_ip > 0 {
+        println!("{}", styled(&format!(
+            "{} non-IP packets could not be matched against --local / --filter", a.non_ip), C_GREY, false));
+    }
+    println!();
+    let summary = Summary::from_monitor(&monitor, stats.total_rx, stats.total_tx, n as f64 * secs);
+    println!("{}", summary.human());
+
+    // Charts need a terminal: they're drawn with cursor-control escapes.
+    if !std::io::stdout().is_terminal() { return Ok(()); }
+    let window = crate::view::Window::fit(n as usize, args);
+    let per_col = match window.per_col() {
+        1 => String::new(),
+        k => format!(" (peak of every {} intervals)", k),
+    };
+    let (c_dl, c_ul) = args.display.colors(&monitor.interface);
+    // The file's link is unknown, so only --link-speed or a rate can fix the scale.
+    let max = args.y_max.and_then(|m| m.resolve(args.link_speed.map(|b| b / 8.0)));
+    let show_both = !args.download && !args.upload;
+    if args.download || show_both {
+        println!();
+        print_chart(&window.series(&monitor.track.history_dl), args.height, c_dl, &format!("▼ Download Speed{}", per_col), None, max);
+        println!();
+    }
+    if args.upload || show_both {
+        println!();
+        print_chart(&window.series(&monitor.track.history_ul), args.height, c_ul, &format!("▲ Upload Speed{}", per_col), None, max);
+        println!();
+    }
+    crate::flush();
+    Ok(())
+}
+
+fn time_of(ns: i64) -> DateTime<Local> {
+    Local.timestamp_nanos(ns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal IPv4/UDP packet from 10.0.0.1:53 to 10.0.0.2:40000.
+    fn ipv4_udp() -> Vec<u8> {
+        let mut p = vec![0x45, 0, 0, 28, 0, 0, 0, 0, 64, packet::PROTO_UDP, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2];
+        p.extend([0, 53, 0x9c, 0x40, 0, 8, 0, 0]);
+        p
+    }
+
+    fn ethernet(tags: &[u16], ethertype: u16, payload: &[u8]) -> Vec<u8> {
+        let mut f = vec![0; 12];
+        for t in tags { f.extend(t.to_be_bytes()); f.extend([0, 42]); }
+        f.extend(ethertype.to_be_bytes());
+        f.extend(payload);
+        f
+    }
+
+    #[test]
+    fn vlan_tags_are_skipped() {
+        for tags in [&[][..], &[0x8100], &[0x88a8, 0x8100]] {
+            let frame = ethernet(tags, 0x0800, &ipv4_udp());
+            let (ip, outgoing) = network_layer(LINKTYPE_ETHERNET, &frame);
+            let p = packet::parse_ip(ip.unwrap(), frame.len()).unwrap();
+            assert_eq!((p.sport, p.dport, outgoing), (53, 40000, None));
+        }
+    }
+
+    #[test]
+    fn non_ip_and_truncated_frames_are_skipped() {
+        assert!(network_layer(LINKTYPE_ETHERNET, &ethernet(&[], 0x0806, &[0; 28])).0.is_none());
+        assert!(network_layer(LINKTYPE_ETHERNET, &ethernet(&[0x8100], 0x0806, &[])).0.is_none());
+        assert!(network_layer(LINKTYPE_ETHERNET, &[0; 13]).0.is_none());
+        assert!(network_layer(LINKTYPE_ETHERNET, &ethernet(&[0x8100], 0x0800, &[])[..16]).0.is_none());
+    }
+
+    // ── Capture files ─────────────────────────────────────────────────────────
+
+    /// What `each` saw of one frame.
+    #[derive(Debug, PartialEq)]
+    struct Seen { ts_ns: i64, link: u32, caplen: usize, len: u64, outgoing: Option<bool> }
+
+    /// Dispatch on the magic like `read_file`, from memory.
+    fn frames(mut file: &[u8]) -> Result<Vec<Seen>> {
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        let mut out = Vec::new();
+        let mut each = |f: &Frame| out.push(Seen {
+            ts_ns: f.ts_ns, link: f.link, caplen: f.data.len(), len: f.len, outgoing: f.outgoing,
+        });
+        match magic {
+            [0x0a, 0x0d, 0x0d, 0x0a] => read_pcapng(&mut file, &mut each)?,
+            m => read_pcap(&mut file, m, &mut each)?,
+        }
+        Ok(out)
+    }
+
+    fn is_eof(e: &anyhow::Error) -> bool {
+        e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == ErrorKind::UnexpectedEof)
+    }
+
+    /// Integers in either byte order.
+    struct Writer { le: bool, out: Vec<u8> }
+
+    impl Writer {
+        fn u16(&mut self, v: u16) -> &mut Self {
+            self.out.extend(if self.le { v.to_le_bytes() } else { v.to_be_bytes() }); self
+        }
+        fn u32(&mut self, v: u32) -> &mut Self {
+            self.out.extend(if self.le { v.to_le_bytes() } else { v.to_be_bytes() }); self
+        }
+        fn bytes(&mut self, b: &[u8]) -> &mut Self {
+            self.out.extend(b); self
+        }
+    }
+
+    /// A classic pcap file; `records` are (sec, frac, data, len).
+    fn pcap(le: bool, nanos: bool, link: u32, records: &[(u32, u32, &[u8], u32)]) -> Vec<u8> {
+        let mut w = Writer { le, out: Vec::new() };
+        w.u32(if nanos { 0xa1b2_3c4d } else { 0xa1b2_c3d4 }).u16(2).u16(4).u32(0).u32(0).u32(65535).u32(link);
+        for (sec, frac, data, len) in records {
+            w.u32(*sec).u32(*frac).u32(data.len() as u32).u32(*len).bytes(data);
+        }
+        w.out
+    }
+
+    /// One pcapng block with its framing; `body` is padded to 32 bits.
+    fn block(le: bool, kind: u32, body: &[u8]) -> Vec<u8> {
+        let total = 12 + body.len().next_multiple_of(4) as u32;
+        let mut w = Writer { le, out: Vec::new() };
+        w.u32(kind).u32(total).bytes(body);
+        w.out.resize(8 + body.len().next_multiple_of(4), 0);
+        w.u32(total);
+        w.out
+    }
+
+    fn shb(le: bool) -> Vec<u8> {
+        let mut w = Writer { le, out: Vec::new() };
+        w.u32(0x1a2b_3c4d).u16(1).u16(0).bytes(&[0xff; 8]);
+        block(le, 0x0a0d_0d0a, &w.out)
+    }
+
+    /// An interface description with `(code, value)` options.
+    fn idb(le: bool, link: u16, snaplen: u32, opts: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut w = Writer { le, out: Vec::new() };
+        w.u16(link).u16(0).u32(snaplen);
+        for (code, v) in opts {
+            w.u16(*code).u16(v.len() as u16).bytes(v);
+            w.out.resize(w.out.len().next_multiple_of(4), 0);
+        }
+        w.u32(0);
+        block(le, 1, &w.out)
+    }
+
+    /// An Enhanced Packet Block, with `epb_flags` when `flags` is set.
+    fn epb(le: bool, iface: u32, units: u64, data: &[u8], len: u32, flags: Option<u32>) -> Vec<u8> {
+        let mut w = Writer { le, out: Vec::new() };
+        w.u32(iface).u32((units >> 32) as u32).u32(units as u32).u32(data.len() as u32).u32(len).bytes(data);
+        w.out.resize(w.out.len().next_multiple_of(4), 0);
+        if let Some(f) = flags { w.u16(2).u16(4).u32(f).u32(0); }
+        block(le, 6, &w.out)
+    }
+
+    fn pcapng(le: bool, blocks: &[Vec<u8>]) -> Vec<u8> {
+        let mut f = shb(le);
+        for b in blocks { f.extend(b); }
+        f
+    }
+
+    #[test]
+    fn pcap_micro_and_nano_in_both_byte_orders() {
+        let ip = ipv4_udp();
+        for le in [true, false] {
+            let f = pcap(le, false, LINKTYPE_RAW | 0x1000_0000, &[(10, 250_000, &ip, 1500), (11, 0, &ip[..20], 60)]);
+            assert_eq!(frames(&f).unwrap(), [
+                Seen { ts_ns: 10_250_000_000, link: LINKTYPE_RAW, caplen: 28, len: 1500, outgoing: None },
+                Seen { ts_ns: 11_000_000_000, link: LINKTYPE_RAW, caplen: 20, len: 60, outgoing: None },
+            ]);
+            let f = pcap(le, true, LINKTYPE_ETHERNET, &[(10, 250, &ip, 28)]);
+            assert_eq!(frames(&f).unwrap()[0].ts_ns, 10_000_000_250);
+        }
+    }
+
+    #[test]
+    fn pcap_malformed() {
+        let ip = ipv4_udp();
+        let f = pcap(true, false, LINKTYPE_RAW, &[(1, 0, &ip, 28)]);
+        // Cut in the file header, a record header, and a packet.
+        for cut in [10, 24 + 8, f.len() - 1] {
+            assert!(is_eof(&frames(&f[..cut]).unwrap_err()), "cut at {}", cut);
+        }
+        // A clean end after a record is fine.
+        assert_eq!(frames(&f).unwrap().len(), 1);
+
+        let mut huge = f.clone();
+        huge[24 + 8..24 + 12].copy_from_slice(&(MAX_RECORD as u32 + 1).to_le_bytes());
+        assert!(!is_eof(&frames(&huge).unwrap_err()));
+        assert!(frames(b"GIF89a-not-a-capture-file").is_err());
+    }
+
+    #[test]
+    fn pcapng_enhanced_packets_and_direction() {
+        let ip = ipv4_udp();
+        for le in [true, false] {
+            let f = pcapng(le, &[
+                idb(le, LINKTYPE_ETHERNET as u16, 0, &[]),
+                epb(le, 0, 5_000_001, &ip, 1514, Some(2)),
+                epb(le, 0, 5_000_002, &ip, 1514, Some(1)),
+                block(le, 5, &[0; 8]),   // statistics: skipped
+                epb(le, 0, 1 << 33, &ip, 28, None),
+            ]);
+            assert_eq!(frames(&f).unwrap(), [
+                Seen { ts_ns: 5_000_001_000, link: LINKTYPE_ETHERNET, caplen: 28, len: 1514, outgoing: Some(true) },
+                Seen { ts_ns: 5_000_002_000, link: LINKTYPE_ETHERNET, caplen: 28, len: 1514, outgoing: Some(false) },
+                Seen { ts_ns: (1 << 33) * 1000, link: LINKTYPE_ETHERNET, caplen: 28, len: 28, outgoing: None },
+            ]);
+        }
+    }
+
+    #[test]
+    fn pcapng_tsresol_and_tsoffset() {
+        let ip = ipv4_udp();
+        let le = true;
+        let f = pcapng(le, &[
+            idb(le, LINKTYPE_RAW as u16, 0, &[(9, &[9])]),                                    // ns
+            idb(le, LINKTYPE_RAW as u16, 0, &[(9, &[0x80 | 10]), (14, &100i64.to_le_bytes())]), // 2^-10 s, +100 s
+            idb(le, LINKTYPE_RAW as u16, 0, &[(9, &[12])]),                                   // ps
+            epb(le, 0, 1_500_000_000, &ip, 28, None),
+            epb(le, 1, 1024 + 512, &ip, 28, None),
+            epb(le, 2, 2_000_000_000_000, &ip, 28, None),
+        ]);
+        let ts: Vec<i64> = frames(&f).unwrap().iter().map(|s| s.ts_ns).collect();
+        assert_eq!(ts, [1_500_000_000, 101_500_000_000, 2_000_000_000]);
+    }
+
+    #[test]
+    fn pcapng_packet_and_simple_packet_blocks() {
+        let ip = ipv4_udp();
+        let le = true;
+        let mut w = Writer { le, out: Vec::new() };
+        w.u16(0).u16(0).u32(0).u32(7_000_000).u32(28).u32(28).bytes(&ip);
+        let pb = block(le, 2, &w.out);
+        let mut w = Writer { le, out: Vec::new() };
+        w.u32(1500).bytes(&ip);
+        let spb = block(le, 3, &w.out);
+        let f = pcapng(le, &[idb(le, LINKTYPE_RAW as u16, 24, &[]), pb, spb]);
+        assert_eq!(frames(&f).unwrap(), [
+            Seen { ts_ns: 7_000_000_000, link: LINKTYPE_RAW, caplen: 28, len: 28, outgoing: None },
+            // No timestamp of its own, and cut to the interface's snaplen.
+            Seen { ts_ns: 7_000_000_000, link: LINKTYPE_RAW, caplen: 24, len: 1500, outgoing: None },
+        ]);
+    }
+
+    #[test]
+    fn pcapng_malformed() {
+        let ip = ipv4_udp();
+        let le = true;
+        // A packet for an interface never described.
+        assert!(frames(&pcapng(le, &[epb(le, 0, 1, &ip, 28, None)])).is_err());
+        assert!(frames(&pcapng(le, &[idb(le, 1, 0, &[]), epb(le, 1, 1, &ip, 28, None)])).is_err());
+        // Block lengths too small, unaligned or enormous.
+        for total in [0u32, 8, 13, MAX_RECORD as u32 + 4] {
+            let mut f = pcapng(le, &[idb(le, 1, 0, &[])]);
+            let at = shb(le).len() + 4;
+            f[at..at + 4].copy_from_slice(&total.to_le_bytes());
+            assert!(frames(&f).is_err(), "block of {} bytes", total);
+        }
+        // A section header too short to hold its own fields.
+        let mut f = shb(le);
+        f[4..8].copy_from_slice(&12u32.to_le_bytes());
+        assert!(frames(&f).is_err());
+        // A byte-order magic that is neither.
+        let mut f = shb(le);
+        f[8] = 0;
+        assert!(frames(&f).is_err());
+        // A captured length beyond the block.
+        let packet = epb(le, 0, 1, &ip, 28, None);
+        let mut f = pcapng(le, &[idb(le, 1, 0, &[]), packet.clone()]);
+        let at = f.len() - packet.len() + 8 + 12;
+        f[at..at + 4].copy_from_slice(&1000u32.to_le_bytes());
+        assert!(frames(&f).is_err());
+    }
+
+    #[test]
+    fn damaged_files_never_panic() {
+        let ip = ipv4_udp();
+        let files = [
+            pcap(true, false, LINKTYPE_ETHERNET, &[(1, 2, &ip, 28), (3, 4, &ip, 28)]),
+            pcapng(false, &[
+                idb(false, 1, 0, &[(9, &[0x85]), (14, &[1; 8])]),
+                epb(false, 0, u64::MAX, &ip, 28, Some(3)),
+                block(false, 3, &[0, 0, 0, 40]),
+            ]),
+        ];
+        for f in &files {
+            for cut in 0..f.len() { let _ = frames(&f[..cut]); }
+            for at in 0..f.len() {
+                for v in [0x00, 0x03, 0xff] {
+                    let mut g = f.clone();
+                    g[at] = v;
+                    let _ = frames(&g);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cooked_and_null_link_layers() {
+        let ip = ipv4_udp();
+        let mut sll = vec![0, 4];   // sent by us
+        sll.extend([0; 12]);
+        sll.extend(0x0800u16.to_be_bytes());
+        sll.extend(&ip);
+        assert_eq!(network_layer(LINKTYPE_LINUX_SLL, &sll), (Some(&ip[..]), Some(true)));
+        sll[1] = 0;
+        assert_eq!(network_layer(LINKTYPE_LINUX_SLL, &sll).1, Some(false));
+
+        let mut sll2 = 0x86ddu16.to_be_bytes().to_vec();
+        sll2.extend([0; 8]);
+        sll2.push(PACKET_OUTGOING);
+        sll2.extend([0; 9]);
+        sll2.extend(&ip);
+        assert_eq!(network_layer(LINKTYPE_LINUX_SLL2, &sll2), (Some(&ip[..]), Some(true)));
+
+        let mut null = 2u32.to_le_bytes().to_vec();
+        null.extend(&ip);
+        assert_eq!(network_layer(LINKTYPE_NULL, &null).0, Some(&ip[..]));
+        assert_eq!(network_layer(LINKTYPE_RAW, &ip).0, Some(&ip[..]));
+        assert_eq!(network_layer(LINKTYPE_LINUX_SLL, &sll[..10]), (None, None));
+        assert_eq!(network_layer(9999, &ip), (None, None));
+    }
+
+    #[test]
+    fn intervals_below_a_millisecond_are_refused() {
+        assert_eq!(parse_interval("1ms"), Ok(MIN_INTERVAL));
+        assert_eq!(parse_interval("100ms"), Ok(Duration::from_millis(100)));
+        assert_eq!(parse_interval("2"), Ok(Duration::from_secs(2)));
+        for bad in ["0", "0s", "0.5ms", "0.0009", "soon"] {
+            assert!(parse_interval(bad).is_err(), "'{}' should be refused", bad);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+// File: src/procs.rs
+//! Per-process traffic on Linux: `--processes [N]` and the `t` key.
+//!
+//! Byte counters come from the kernel's socket diagnostics (a `sock_diag`
+//! netlink dump of TCP sockets with `tcp_info`: `bytes_received` for
+//! download, `bytes_acked` for upload). Sockets are tied to processes by
+//! inode: `/proc/net/{tcp,tcp6,udp,udp6}` list the network sockets with
+//! their local address, and `/proc/<pid>/fd/*` link to `socket:[inode]`.
+//! Only sockets bound to an address of the monitored interface count, so
+//! the table agrees with the charts (`-i lo` shows loopback transfers).
+//!
+//! UDP has no byte counters in `sock_diag`, so UDP sockets add to a
+//! process's socket count but not to its rates. Without root the fds of
+//! other users' processes can't be read; their traffic is summed on one
+//! "(other users)" row instead of disappearing.
+
+use crate::{clear_to_eol, fmt_bps, styled, C_CYAN, C_GREY, C_WHITE};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Instant;
+
+/// Rows shown by a bare `--processes`.
+pub const DEFAULT_TOP: usize = 10;
+
+/// Traffic of one process over the last interval.
+#[derive(Debug, Clone)]
+pub struct Usage {
+    pub pid:     Option<u32>,   // None: sockets whose owner we can't see
+    pub name:    String,
+    pub sockets: usize,
+    pub rx_bps:  f64,
+    pub tx_bps:  f64,
+}
+
+pub struct Processes {
+    prev:      HashMap<u64, (u64, u64)>,  // socket inode → (received, acked)
+    prev_time: Instant,
+    pub top:   Vec<Usage>,                // busiest first
+}
+
+impl Processes {
+    /// Fails where socket diagnostics aren't available (non-Linux, or a
+    /// kernel without `inet_diag`).
+    pub fn new() -> Result<Self> {
+        let prev = sys::tcp_counters()?;
+        Ok(Self { prev, prev_time: Instant::now(), top: Vec::new() })
+    }
+
+    /// Refresh the table. `addrs` are the monitored interface's addresses.
+    pub fn sample(&mut self, addrs: &[IpAddr]) {
+        let now = Instant::now();
+        let secs = now.duration_since(self.prev_time).as_secs_f64();
+        let Ok(counters) = sys::tcp_counters() else { return };
+        let sockets = sys::sockets(addrs);
+        let owners = sys::owners(&sockets);
+
+        let mut by_pid: HashMap<Option<u32>, Usage> = HashMap::new();
+        for inode in sockets.keys() {
+            let pid = owners.get(inode).copied();
+            let u = by_pid.entry(pid).or_insert_with(|| Usage {
+                pid,
+                name:    pid.map_or_else(|| "(other users)".to_string(), sys::comm),
+                sockets: 0,
+                rx_bps:  0.0,
+                tx_bps:  0.0,
+            });
+            u.sockets += 1;
+            let Some(&(rx, tx)) = counters.get(inode) else { continue };
+            // A socket opened during the interval counts from zero.
+            let (prx, ptx) = self.prev.get(inode).copied().unwrap_or((0, 0));
+            if secs > 0.0 {
+                u.rx_bps += rx.saturating_sub(prx) as f64 / secs;
+                u.tx_bps += tx.saturating_sub(ptx) as f64 / secs;
+            }
+        }
+
+        let mut top: Vec<Usage> = by_pid.into_values().collect();
+        top.sort_by(|a, b| (b.rx_bps + b.tx_bps).total_cmp(&(a.rx_bps + a.tx_bps))
+            .then(b.sockets.cmp(&a.sockets)));
+        self.top = top;
+        self.prev = counters;
+        self.prev_time = now;
+    }
+
+    /// The top-`n` table, continuing below the last chart line.
+    pub fn draw(&self, n: usize) {
+        print!("\r\n");
+        clear_to_eol();
+        print!("\r\n{}", styled(&format!("{:>7}  {:<16} {:>5}  {:>14}  {:>14}",
+            "PID", "PROCESS", "SOCK", "↓ Download", "↑ Upload"), C_CYAN, true));
+        clear_to_eol();
+        if self.top.is_empty() {
+            print!("\r\n{}", styled("  (no sockets on this interface)", C_GREY, false));
+            clear_to_eol();
+        }
+        for u in self.top.iter().take(n) {
+            let pid = u.pid.map_or_else(|| "-".to_string(), |p| p.to_string());
+            let name: String = u.name.chars().take(16).collect();
+            let idle = u.rx_bps + u.tx_bps == 0.0;
+            print!("\r\n{}", styled(&format!("{:>7}  {:<16} {:>5}  {:>14}  {:>14}",
+                pid, name, u.sockets, fmt_bps(u.rx_bps), fmt_bps(u.tx_bps)),
+                if idle { C_GREY } else { C_WHITE }, false));
+            clear_to_eol();
+        }
+        crate::clear_to_eos();
+        crate::flush();
+    }
+}
+
+// ── Linux ─────────────────────────────────────────────────────────────────────
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use anyhow::{bail, Result};
+    use std::collections::HashMap;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    const SOCK_DIAG_BY_FAMILY: u16 = 20;
+    const INET_DIAG_INFO: u16 = 2;
+    const NLMSG_ERROR: u16 = 2;
+    const NLMSG_DONE: u16 = 3;
+    /// `struct inet_diag_msg`: inode at 68, attributes from 72.
+    const DIAG_MSG_LEN: usize = 72;
+    /// `tcp_info.tcpi_bytes_acked` / `tcpi_bytes_received` (Linux 4.1+).
+    const BYTES_ACKED: usize = 120;
+    const BYTES_RECEIVED: usize = 128;
+    /// Every TCP state.
+    const ALL_STATES: u32 = 0xfff;
+
+    /// (received, acked) bytes of every TCP socket, by inode.
+    pub fn tcp_counters() -> Result<HashMap<u64, (u64, u64)>> {
+        let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_SOCK_DIAG) };
+        if fd < 0 { bail!("sock_diag netlink socket: {}", std::io::Error::last_os_error()); }
+        let result = (|| {
+            let mut out = HashMap::new();
+            for family in [libc::AF_INET, libc::AF_INET6] {
+                dump(fd, family as u8, &mut out)?;
+            }
+            Ok(out)
+        })();
+        unsafe { libc::close(fd) };
+        result
+    }
+
+    /// A dump request for every TCP socket of `family`, with `tcp_info`:
+    /// nlmsghdr (16) + inet_diag_req_v2 (56), all in host byte order.
+    fn request(family: u8) -> Vec<u8> {
+        let mut req = Vec::with_capacity(72);
+        req.extend_from_slice(&72u32.to_ne_bytes());
+        req.extend_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+        req.extend_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+        req.extend_from_slice(&[0; 8]);  // seq, pid
+        req.extend_from_slice(&[family, libc::IPPROTO_TCP as u8, 1 << (INET_DIAG_INFO - 1), 0]);
+        req.extend_from_slice(&ALL_STATES.to_ne_bytes());
+        req.extend_from_slice(&[0; 48]);  // inet_diag_sockid: match everything
+        req
+    }
+
+    fn dump(fd: i32, family: u8, out: &mut HashMap<u64, (u64, u64)>) -> Result<()> {
+        let req = request(family);
+        if unsafe { libc::send(fd, req.as_ptr().cast(), req.len(), 0) } < 0 {
+            bail!("sock_diag request: {}", std::io::Error::last_os_error());
+        }
+
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = unsafe { libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), 0) };
+            if n < 0 { bail!("sock_diag reply: {}", std::io::Error::last_os_error()); }
+            if n == 0 { return Ok(()); }
+            let mut msgs = &buf[..n as usize];
+            while msgs.len() >= 16 {
+                let len = u32_at(msgs, 0) as usize;
+                let kind = u16::from_ne_bytes([msgs[4], msgs[5]]);
+                if len < 16 || len > msgs.len() { break; }
+                match kind {
+                    NLMSG_DONE  => return Ok(()),
+                    NLMSG_ERROR => bail!("sock_diag is not supported by this kernel"),
+                    _           => parse(&msgs[16..len], out),
+                }
+                msgs = &msgs[align(len).min(msgs.len())..];
+            }
+        }
+    }
+
+    /// One `inet_diag_msg` and its attributes.
+    fn parse(msg: &[u8], out: &mut HashMap<u64, (u64, u64)>) {
+        if msg.len() < DIAG_MSG_LEN { return; }
+        let inode = u32_at(msg, 68) as u64;
+        let mut attrs = &msg[DIAG_MSG_LEN..];
+        while attrs.len() >= 4 {
+            let len = u16::from_ne_bytes([attrs[0], attrs[1]]) as usize;
+            let kind = u16::from_ne_bytes([attrs[2], attrs[3]]);
+            if len < 4 || len > attrs.len() { break; }
+            let data = &attrs[4..len];
+            if kind == INET_DIAG_INFO && data.len() >= BYTES_RECEIVED + 8 {
+                out.insert(inode, (u64_at(data, BYTES_RECEIVED), u64_at(data, BYTES_ACKED)));
+            }
+            attrs = &attrs[align(len).min(attrs.len())..];
+        }
+    }
+
+    fn align(n: usize) -> usize { (n + 3) & !3 }
+    fn u32_at(b: &[u8], i: usize) -> u32 { u32::from_ne_bytes(b[i..i + 4].try_into().unwrap()) }
+    fn u64_at(b: &[u8], i: usize) -> u64 { u64::from_ne_bytes(b[i..i + 8].try_into().unwrap()) }
+
+    /// Network sockets bound to one of `addrs`: inode → local address.
+    pub fn sockets(addrs: &[IpAddr]) -> HashMap<u64, IpAddr> {
+        let mut out = HashMap::new();
+        for table in ["tcp", "tcp6", "udp", "udp6"] {
+            let Ok(text) = std::fs::read_to_string(format!("/proc/net/{}", table)) else { continue };
+            for line in text.lines().skip(1) {
+                let f: Vec<&str> = line.split_whitespace().collect();
+                if f.len() < 10 { continue; }
+                let Some(addr) = f[1].split(':').next().and_then(hex_addr) else { continue };
+                let Ok(inode) = f[9].parse::<u64>() else { continue };
+                if inode != 0 && addrs.contains(&addr) { out.insert(inode, addr); }
+            }
+        }
+        out
+    }
+
+    /// `0100007F` or a 32-digit IPv6 address, as the kernel prints them:
+    /// 32-bit words in host byte order. IPv4-mapped IPv6 comes back as IPv4.
+    fn hex_addr(s: &str) -> Option<IpAddr> {
+        let word = |i: usize| u32::from_str_radix(s.get(i * 8..i * 8 + 8)?, 16).ok().map(u32::to_ne_bytes);
+        match s.len() {
+            8  => Some(IpAddr::V4(Ipv4Addr::from(word(0)?))),
+            32 => {
+                let mut b = [0u8; 16];
+                for i in 0..4 { b[i * 4..i * 4 + 4].copy_from_slice(&word(i)?); }
+                let v6 = Ipv6Addr::from(b);
+                Some(v6.to_ipv4_mapped().map_or(IpAddr::V6(v6), IpAddr::V4))
+            }
+            _ => None,
+        }
+    }
+
+    /// Owning pid of each socket in `sockets`, from `/proc/<pid>/fd`.
+    pub fn owners(sockets: &HashMap<u64, IpAddr>) -> HashMap<u64, u32> {
+        let mut out = HashMap::new();
+        let Ok(procs) = std::fs::read_dir("/proc") else { return out };
+        for p in procs.flatten() {
+            let Some(pid) = p.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else { continue };
+            let Ok(fds) = std::fs::read_dir(p.path().join("fd")) else { continue };
+            for fd in fds.flatten() {
+                let Ok(target) = std::fs::read_link(fd.path()) else { continue };
+                let inode = target.to_str()
+                    .and_then(|t| t.strip_prefix("socket:["))
+                    .and_then(|t| t.strip_suffix(']'))
+                    .and_then(|t| t.parse::<u64>().ok());
+                if let Some(inode) = inode.filter(|i| sockets.contains_key(i)) {
+                    out.entry(inode).or_insert(pid);
+                }
+            }
+        }
+        out
+    }
+
+    pub fn comm(pid: u32) -> String {
+        std::fs::read_to_string(format!("/proc/{}/comm", pid))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|_| "?".to_string())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::procs::Processes;
+        use std::io::{Read, Write};
+        use std::net::{TcpListener, TcpStream};
+        use std::os::fd::AsRawFd;
+
+        /// An `inet_diag_msg` for `inode`, then one attribute per `(kind, data)`.
+        fn diag_msg(inode: u32, attrs: &[(u16, Vec<u8>)]) -> Vec<u8> {
+            let mut m = vec![0; 68];
+            m.extend(inode.to_ne_bytes());
+            for (kind, data) in attrs {
+                m.extend((4 + data.len() as u16).to_ne_bytes());
+                m.extend(kind.to_ne_bytes());
+                m.extend(data);
+                m.resize(align(m.len()), 0);
+            }
+            m
+        }
+
+        fn tcp_info(received: u64, acked: u64) -> Vec<u8> {
+            let mut info = vec![0; BYTES_RECEIVED + 8];
+            info[BYTES_ACKED..BYTES_ACKED + 8].copy_from_slice(&acked.to_ne_bytes());
+            info[BYTES_RECEIVED..].copy_from_slice(&received.to_ne_bytes());
+            info
+        }
+
+        fn inode(fd: &impl AsRawFd) -> u64 {
+            let link = std::fs::read_link(format!("/proc/self/fd/{}", fd.as_raw_fd())).unwrap();
+            link.to_str().unwrap().trim_start_matches("socket:[").trim_end_matches(']').parse().unwrap()
+        }
+
+        #[test]
+        fn request_layout() {
+            let req = request(libc::AF_INET6 as u8);
+            assert_eq!(req.len(), 72);
+            assert_eq!(u32_at(&req, 0), 72);
+            assert_eq!(u16::from_ne_bytes([req[4], req[5]]), SOCK_DIAG_BY_FAMILY);
+            assert_eq!(u16::from_ne_bytes([req[6], req[7]]), (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16);
+            assert_eq!(&req[16..20], &[libc::AF_INET6 as u8, libc::IPPROTO_TCP as u8, 0b10, 0]);
+            assert_eq!(u32_at(&req, 20), ALL_STATES);
+        }
+
+        #[test]
+        fn counters_come_from_the_info_attribute() {
+            let mut out = HashMap::new();
+            // An unrelated attribute before tcp_info is skipped.
+            parse(&diag_msg(7, &[(1, vec![9; 5]), (INET_DIAG_INFO, tcp_info(1000, 200))]), &mut out);
+            assert_eq!(out.get(&7), Some(&(1000, 200)));
+
+            // No tcp_info, a short one, a truncated header, a bogus attribute length.
+            parse(&diag_msg(8, &[]), &mut out);
+            parse(&diag_msg(9, &[(INET_DIAG_INFO, vec![0; BYTES_RECEIVED])]), &mut out);
+            parse(&diag_msg(10, &[])[..60], &mut out);
+            let mut bad = diag_msg(11, &[(INET_DIAG_INFO, tcp_info(1, 1))]);
+            bad[DIAG_MSG_LEN..DIAG_MSG_LEN + 2].copy_from_slice(&500u16.to_ne_bytes());
+            parse(&bad, &mut out);
+            assert_eq!(out.len(), 1);
+        }
+
+        #[test]
+        fn proc_net_addresses() {
+            assert_eq!(hex_addr(&hex_word([127, 0, 0, 1])), Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+            let mapped: String = [[0; 4], [0; 4], [0, 0, 0xff, 0xff], [10, 0, 0, 5]].map(hex_word).concat();
+            assert_eq!(hex_addr(&mapped), Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5))));
+            let v6: String = [[0x20, 0x01, 0x0d, 0xb8], [0; 4], [0; 4], [0, 0, 0, 1]].map(hex_word).concat();
+            assert_eq!(hex_addr(&v6), "2001:db8::1".parse().ok());
+            assert_eq!(hex_addr("0100"), None);
+            assert_eq!(hex_addr("zz00007F"), None);
+        }
+
+        /// A 32-bit word as the kernel prints it: host byte order, in hex.
+        fn hex_word(b: [u8; 4]) -> String {
+            format!("{:08X}", u32::from_ne_bytes(b))
+        }
+
+        #[test]
+        fn loopback_transfer_is_ours() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            let (mut server, _) = listener.accept().unwrap();
+            let mut procs = Processes::new().unwrap();
+
+            client.write_all(&[0; 100_000]).unwrap();
+            server.read_exact(&mut [0; 100_000]).unwrap();
+
+            let counters = tcp_counters().unwrap();
+            let (rx, _) = counters[&inode(&server)];
+            let (_, tx) = counters[&inode(&client)];
+            assert!(rx >= 100_000 && tx >= 100_000, "received {}, acked {}", rx, tx);
+
+            let lo = [IpAddr::V4(Ipv4Addr::LOCALHOST)];
+            let sockets = sockets(&lo);
+            let owners = owners(&sockets);
+            for s in [inode(&client), inode(&server), inode(&listener)] {
+                assert_eq!(owners.get(&s), Some(&std::process::id()));
+            }
+
+            procs.sample(&lo);
+            let me = procs.top.iter().find(|u| u.pid == Some(std::process::id())).unwrap();
+            assert!(me.sockets >= 3);
+            assert!(me.rx_bps > 0.0 && me.tx_bps > 0.0);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use anyhow::{bail, Result};
+    use std::collections::HashMap;
+    use std::net::IpAddr;
+
+    pub fn tcp_counters() -> Result<HashMap<u64, (u64, u64)>> {
+        bail!("Per-process usage is only available on Linux")
+    }
+    pub fn sockets(_addrs: &[IpAddr]) -> HashMap<u64, IpAddr> { HashMap::new() }
+    pub fn owners(_sockets: &HashMap<u64, IpAddr>) -> HashMap<u64, u32> { HashMap::new() }
+    pub fn comm(_pid: u32) -> String { String::new() }
+}
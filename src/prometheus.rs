@@ -0,0 +1,282 @@
+// File: src/prometheus.rs
+//! Prometheus `/metrics` endpoint.
+//!
+//! A `Registry` is fed every interval with the samples of all interfaces and
+//! renders them in the text exposition format (v0.0.4). The HTTP side is a
+//! deliberately tiny blocking server on its own thread: one request per
+//! connection, GET only, which is all a Prometheus scraper needs and keeps us
+//! free of an async runtime.
+
+use crate::BandwidthStats;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const QUANTILES: &[f64] = &[0.5, 0.9, 0.95, 0.99];
+
+type CounterFn = fn(&BandwidthStats) -> u64;
+type GaugeFn   = fn(&IfaceMetrics) -> f64;
+
+// ── Percentiles ───────────────────────────────────────────────────────────────
+
+/// Nearest-rank percentile (`q` in 0..=1). Returns 0 for an empty slice.
+pub fn percentile(values: &[f64], q: f64) -> f64 {
+    if values.is_empty() { return 0.0; }
+    let mut v = values.to_vec();
+    v.sort_by(|a, b| a.total_cmp(b));
+    let rank = (q.clamp(0.0, 1.0) * v.len() as f64).ceil() as usize;
+    v[rank.saturating_sub(1).min(v.len() - 1)]
+}
+
+// ── Registry ──────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct IfaceMetrics {
+    last:    Option<BandwidthStats>,
+    peak_rx: f64,
+    peak_tx: f64,
+    sum_rx:  f64,
+    sum_tx:  f64,
+    count:   u64,
+    window:  VecDeque<(f64, f64)>,  // recent (rx, tx) rates for quantiles
+}
+
+pub struct Registry {
+    window: usize,
+    ifaces: BTreeMap<String, IfaceMetrics>,
+}
+
+impl Registry {
+    /// `window` bounds how many recent samples the quantiles are computed over.
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), ifaces: BTreeMap::new() }
+    }
+
+    pub fn observe(&mut self, samples: &[(String, BandwidthStats)]) {
+        for (name, s) in samples {
+            let m = self.ifaces.entry(name.clone()).or_default();
+            m.last = Some(s.clone());
+            if s.elapsed < 0.001 { continue; }  // counters only — no rate yet
+            m.peak_rx = m.peak_rx.max(s.download_bps);
+            m.peak_tx = m.peak_tx.max(s.upload_bps);
+            m.sum_rx += s.download_bps;
+            m.sum_tx += s.upload_bps;
+            m.count  += 1;
+            if m.window.len() >= self.window { m.window.pop_front(); }
+            m.window.push_back((s.download_bps, s.upload_bps));
+        }
+    }
+
+    /// Text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let counters: &[(&str, &str, CounterFn)] = &[
+            ("receive_bytes_total",    "Bytes received",         |s| s.total_rx),
+            ("transmit_bytes_total",   "Bytes transmitted",      |s| s.total_tx),
+            ("receive_packets_total",  "Packets received",       |s| s.rx_packets),
+            ("transmit_packets_total", "Packets transmitted",    |s| s.tx_packets),
+            ("receive_errors_total",   "Receive errors",         |s| s.rx_errors),
+            ("transmit_errors_total",  "Transmit errors",        |s| s.tx_errors),
+        ];
+        for (name, help, get) in counters {
+            header(&mut out, name, help, "counter");
+            for (iface, m) in &self.ifaces {
+                if let Some(ref s) = m.last {
+                    let _ = writeln!(out, "bandwidthmon_{}{{interface=\"{}\"}} {}", name, escape(iface), get(s));
+                }
+            }
+        }
+
+        let gauges: &[(&str, &str, GaugeFn)] = &[
+            ("receive_bytes_per_second",       "Current receive rate",      |m| m.last.as_ref().map_or(0.0, |s| s.download_bps)),
+            ("transmit_bytes_per_second",      "Current transmit rate",     |m| m.last.as_ref().map_or(0.0, |s| s.upload_bps)),
+            ("receive_peak_bytes_per_second",  "Session peak receive rate", |m| m.peak_rx),
+            ("transmit_peak_bytes_per_second", "Session peak transmit rate", |m| m.peak_tx),
+        ];
+        for (name, help, get) in gauges {
+            header(&mut out, name, help, "gauge");
+            for (iface, m) in &self.ifaces {
+                let _ = writeln!(out, "bandwidthmon_{}{{interface=\"{}\"}} {}", name, escape(iface), get(m));
+            }
+        }
+
+        for (dir, pick) in [("receive", 0usize), ("transmit", 1usize)] {
+            let name = format!("{}_rate_bytes_per_second", dir);
+            header(&mut out, &name, &format!("Recent {} rate distribution", dir), "summary");
+            for (iface, m) in &self.ifaces {
+                let rates: Vec<f64> = m.window.iter().map(|w| if pick == 0 { w.0 } else { w.1 }).collect();
+                let iface = escape(iface);
+                for q in QUANTILES {
+                    let _ = writeln!(out, "bandwidthmon_{}{{interface=\"{}\",quantile=\"{}\"}} {}",
+                        name, iface, q, percentile(&rates, *q));
+                }
+                let sum = if pick == 0 { m.sum_rx } else { m.sum_tx };
+                let _ = writeln!(out, "bandwidthmon_{}_sum{{interface=\"{}\"}} {}", name, iface, sum);
+                let _ = writeln!(out, "bandwidthmon_{}_count{{interface=\"{}\"}} {}", name, iface, m.count);
+            }
+        }
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP bandwidthmon_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE bandwidthmon_{} {}", name, kind);
+}
+
+/// Label values escape backslash, double quote and newline.
+fn escape(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// ── HTTP ──────────────────────────────────────────────────────────────────────
+
+/// Bind `addr` and serve the registry from a background thread. Binding
+/// happens up front so a bad address fails at startup, not silently later.
+pub fn serve(addr: SocketAddr, registry: Arc<Mutex<Registry>>) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .with_context(|| format!("Cannot listen on {}", addr))?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = handle(stream, &registry);
+        }
+    });
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, registry: &Mutex<Registry>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // Drain headers so the client doesn't see a reset before our reply.
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 { line.clear(); }
+
+    let mut parts = request.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = path.split('?').next().unwrap_or("");
+
+    let (status, ctype, body) = match (method, path) {
+        ("GET", "/metrics") => {
+            let body = registry.lock().map(|r| r.render()).unwrap_or_default();
+            ("200 OK", "text/plain; version=0.0.4; charset=utf-8", body)
+        }
+        ("GET", "/") => ("200 OK", "text/html; charset=utf-8",
+            "<html><body><a href=\"/metrics\">metrics</a></body></html>\n".to_string()),
+        ("GET", _)   => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _            => ("405 Method Not Allowed", "text/plain", "method not allowed\n".to_string()),
+    };
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, ctype, body.len(), body)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        assert_eq!(percentile(&[], 0.5), 0.0);
+        assert_eq!(percentile(&[7.0], 0.0), 7.0);
+        assert_eq!(percentile(&[7.0], 0.5), 7.0);
+        assert_eq!(percentile(&[7.0], 1.0), 7.0);
+        let v = [5.0, 1.0, 4.0, 2.0, 3.0];
+        assert_eq!(percentile(&v, 0.0), 1.0);
+        assert_eq!(percentile(&v, 0.5), 3.0);
+        assert_eq!(percentile(&v, 0.9), 5.0);
+        assert_eq!(percentile(&v, 1.0), 5.0);
+        assert_eq!(percentile(&v, 1.5), 5.0);
+        assert_eq!(percentile(&v, -1.0), 1.0);
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape("eth0"), "eth0");
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn renders_text_format() {
+        let s1 = BandwidthStats {
+            download_bps: 100.0, upload_bps: 10.0, total_rx: 1000, total_tx: 200,
+            rx_packets: 5, tx_packets: 3, elapsed: 1.0, ..BandwidthStats::default()
+        };
+        let s2 = BandwidthStats {
+            download_bps: 300.0, upload_bps: 30.0, total_rx: 1300, total_tx: 230,
+            rx_packets: 9, tx_packets: 4, rx_errors: 1, elapsed: 1.0, ..BandwidthStats::default()
+        };
+        let mut r = Registry::new(2);
+        // The first sample of an interface has counters but no rate yet.
+        r.observe(&[("a\"b\\c".to_string(), BandwidthStats { total_rx: 900, ..BandwidthStats::default() })]);
+        r.observe(&[("a\"b\\c".to_string(), s1)]);
+        r.observe(&[("a\"b\\c".to_string(), s2)]);
+
+        let want = r#"# HELP bandwidthmon_receive_bytes_total Bytes received
+# TYPE bandwidthmon_receive_bytes_total counter
+bandwidthmon_receive_bytes_total{interface="a\"b\\c"} 1300
+# HELP bandwidthmon_transmit_bytes_total Bytes transmitted
+# TYPE bandwidthmon_transmit_bytes_total counter
+bandwidthmon_transmit_bytes_total{interface="a\"b\\c"} 230
+# HELP bandwidthmon_receive_packets_total Packets received
+# TYPE bandwidthmon_receive_packets_total counter
+bandwidthmon_receive_packets_total{interface="a\"b\\c"} 9
+# HELP bandwidthmon_transmit_packets_total Packets transmitted
+# TYPE bandwidthmon_transmit_packets_total counter
+bandwidthmon_transmit_packets_total{interface="a\"b\\c"} 4
+# HELP bandwidthmon_receive_errors_total Receive errors
+# TYPE bandwidthmon_receive_errors_total counter
+bandwidthmon_receive_errors_total{interface="a\"b\\c"} 1
+# HELP bandwidthmon_transmit_errors_total Transmit errors
+# TYPE bandwidthmon_transmit_errors_total counter
+bandwidthmon_transmit_errors_total{interface="a\"b\\c"} 0
+# HELP bandwidthmon_receive_bytes_per_second Current receive rate
+# TYPE bandwidthmon_receive_bytes_per_second gauge
+bandwidthmon_receive_bytes_per_second{interface="a\"b\\c"} 300
+# HELP bandwidthmon_transmit_bytes_per_second Current transmit rate
+# TYPE bandwidthmon_transmit_bytes_per_second gauge
+bandwidthmon_transmit_bytes_per_second{interface="a\"b\\c"} 30
+# HELP bandwidthmon_receive_peak_bytes_per_second Session peak receive rate
+# TYPE bandwidthmon_receive_peak_bytes_per_second gauge
+bandwidthmon_receive_peak_bytes_per_second{interface="a\"b\\c"} 300
+# HELP bandwidthmon_transmit_peak_bytes_per_second Session peak transmit rate
+# TYPE bandwidthmon_transmit_peak_bytes_per_second gauge
+bandwidthmon_transmit_peak_bytes_per_second{interface="a\"b\\c"} 30
+# HELP bandwidthmon_receive_rate_bytes_per_second Recent receive rate distribution
+# TYPE bandwidthmon_receive_rate_bytes_per_second summary
+bandwidthmon_receive_rate_bytes_per_second{interface="a\"b\\c",quantile="0.5"} 100
+bandwidthmon_receive_rate_bytes_per_second{interface="a\"b\\c",quantile="0.9"} 300
+bandwidthmon_receive_rate_bytes_per_second{interface="a\"b\\c",quantile="0.95"} 300
+bandwidthmon_receive_rate_bytes_per_second{interface="a\"b\\c",quantile="0.99"} 300
+bandwidthmon_receive_rate_bytes_per_second_sum{interface="a\"b\\c"} 400
+bandwidthmon_receive_rate_bytes_per_second_count{interface="a\"b\\c"} 2
+# HELP bandwidthmon_transmit_rate_bytes_per_second Recent transmit rate distribution
+# TYPE bandwidthmon_transmit_rate_bytes_per_second summary
+bandwidthmon_transmit_rate_bytes_per_second{interface="a\"b\\c",quantile="0.5"} 10
+bandwidthmon_transmit_rate_bytes_per_second{interface="a\"b\\c",quantile="0.9"} 30
+bandwidthmon_transmit_rate_bytes_per_second{interface="a\"b\\c",quantile="0.95"} 30
+bandwidthmon_transmit_rate_bytes_per_second{interface="a\"b\\c",quantile="0.99"} 30
+bandwidthmon_transmit_rate_bytes_per_second_sum{interface="a\"b\\c"} 40
+bandwidthmon_transmit_rate_bytes_per_second_count{interface="a\"b\\c"} 2
+"#;
+        assert_eq!(r.render(), want);
+    }
+
+    #[test]
+    fn quantile_window_is_bounded() {
+        let mut r = Registry::new(2);
+        for rate in [1000.0, 1.0, 2.0] {
+            r.observe(&[("eth0".to_string(), BandwidthStats { download_bps: rate, elapsed: 1.0, ..BandwidthStats::default() })]);
+        }
+        let text = r.render();
+        assert!(text.contains("bandwidthmon_receive_rate_bytes_per_second{interface=\"eth0\",quantile=\"0.99\"} 2\n"));
+        assert!(text.contains("bandwidthmon_receive_peak_bytes_per_second{interface=\"eth0\"} 1000\n"));
+        assert!(text.contains("bandwidthmon_receive_rate_bytes_per_second_count{interface=\"eth0\"} 3\n"));
+    }
+}
@@ -0,0 +1,238 @@
+// File: src/push.rs
+//! StatsD (UDP) and Graphite plaintext (TCP) push exporters.
+//!
+//! Metric names are hierarchical so they work with every StatsD / Carbon
+//! flavour without tag support:
+//!
+//!   <prefix>.<host>.<interface>.rx_bps          gauge, bytes/s
+//!   <prefix>.<host>.<interface>.rx_bytes        StatsD: counter increment
+//!                                               Graphite: cumulative total
+//!
+//! The socket lives on a background thread fed through a small bounded
+//! channel. If the endpoint is slow or down, batches are dropped rather than
+//! queued — the caller (the TUI loop) never blocks on the network.
+
+use crate::BandwidthStats;
+use anyhow::{bail, Result};
+use std::io::Write;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Batches waiting for the sender thread before new ones are dropped.
+const QUEUE_DEPTH: usize = 16;
+
+/// Stay under the common 1500-byte MTU once IP/UDP headers are added.
+const MAX_DATAGRAM: usize = 1432;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_BACKOFF:     Duration = Duration::from_secs(60);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Protocol { Statsd, Graphite }
+
+pub struct Pusher {
+    protocol: Protocol,
+    prefix:   String,
+    tx:       SyncSender<Vec<String>>,
+}
+
+impl Pusher {
+    /// `endpoint` is `HOST:PORT`; the name is resolved on the sender thread
+    /// (and re-resolved on reconnect), so DNS hiccups never block startup.
+    pub fn start(protocol: Protocol, endpoint: &str, prefix: &str, host: &str) -> Result<Self> {
+        match endpoint.rsplit_once(':') {
+            Some((h, p)) if !h.is_empty() && p.parse::<u16>().is_ok() => {}
+            _ => bail!("Invalid endpoint '{}': expected HOST:PORT", endpoint),
+        }
+        let (tx, rx) = sync_channel(QUEUE_DEPTH);
+        let endpoint = endpoint.to_string();
+        std::thread::spawn(move || match protocol {
+            Protocol::Statsd   => statsd_loop(&endpoint, rx),
+            Protocol::Graphite => graphite_loop(&endpoint, rx),
+        });
+        let prefix = [sanitise(prefix), sanitise(host)]
+            .into_iter().filter(|s| !s.is_empty()).collect::<Vec<_>>().join(".");
+        Ok(Self { protocol, prefix, tx })
+    }
+
+    pub fn push(&self, samples: &[(String, BandwidthStats)]) {
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let mut lines = Vec::with_capacity(samples.len() * 4);
+        for (name, s) in samples {
+            if s.elapsed < 0.001 { continue; }
+            let base = format!("{}.{}", self.prefix, sanitise(name));
+            match self.protocol {
+                Protocol::Statsd => {
+                    lines.push(format!("{}.rx_bps:{:.2}|g", base, s.download_bps));
+                    lines.push(format!("{}.tx_bps:{:.2}|g", base, s.upload_bps));
+                    lines.push(format!("{}.rx_bytes:{}|c", base, s.rx_delta));
+                    lines.push(format!("{}.tx_bytes:{}|c", base, s.tx_delta));
+                }
+                Protocol::Graphite => {
+                    lines.push(format!("{}.rx_bps {:.2} {}", base, s.download_bps, ts));
+                    lines.push(format!("{}.tx_bps {:.2} {}", base, s.upload_bps, ts));
+                    lines.push(format!("{}.rx_bytes {} {}", base, s.total_rx, ts));
+                    lines.push(format!("{}.tx_bytes {} {}", base, s.total_tx, ts));
+                }
+            }
+        }
+        if !lines.is_empty() {
+            let _ = self.tx.try_send(lines);  // full or thread gone: drop
+        }
+    }
+}
+
+/// Default for `--metric-host`: the system host name, or "unknown".
+pub fn default_host() -> String {
+    sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Keep metric path components to `[A-Za-z0-9_-]`; dots would add levels.
+fn sanitise(s: &str) -> String {
+    s.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect()
+}
+
+fn resolve(endpoint: &str) -> Option<SocketAddr> {
+    endpoint.to_socket_addrs().ok()?.next()
+}
+
+// ── Sender threads ────────────────────────────────────────────────────────────
+
+fn statsd_loop(endpoint: &str, rx: Receiver<Vec<String>>) {
+    let mut sock: Option<UdpSocket> = None;
+    for lines in rx {
+        let Some(addr) = resolve(endpoint) else { continue };
+        // Bind to the family of the resolved address (IPv4 vs IPv6).
+        if sock.as_ref().and_then(|s| s.local_addr().ok()).is_none_or(|l| l.is_ipv4() != addr.is_ipv4()) {
+            let local: SocketAddr = if addr.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+            sock = UdpSocket::bind(local).ok();
+        }
+        let Some(ref sock) = sock else { continue };
+        // Pack several metrics per datagram, newline-separated.
+        let mut buf = String::new();
+        for l in lines {
+            if !buf.is_empty() && buf.len() + 1 + l.len() > MAX_DATAGRAM {
+                let _ = sock.send_to(buf.as_bytes(), addr);
+                buf.clear();
+            }
+            if !buf.is_empty() { buf.push('\n'); }
+            buf.push_str(&l);
+        }
+        if !buf.is_empty() { let _ = sock.send_to(buf.as_bytes(), addr); }
+    }
+}
+
+fn graphite_loop(endpoint: &str, rx: Receiver<Vec<String>>) {
+    let mut conn: Option<TcpStream> = None;
+    let mut backoff = Duration::from_secs(1);
+    let mut retry_at = Instant::now();
+
+    for lines in rx {
+        if conn.is_none() {
+            if Instant::now() < retry_at { continue; }
+            conn = resolve(endpoint)
+                .and_then(|a| TcpStream::connect_timeout(&a, CONNECT_TIMEOUT).ok());
+            if let Some(ref c) = conn {
+                let _ = c.set_write_timeout(Some(CONNECT_TIMEOUT));
+                backoff = Duration::from_secs(1);
+            } else {
+                retry_at = Instant::now() + backoff;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+        }
+        let mut payload = lines.join("\n");
+        payload.push('\n');
+        if let Some(ref mut c) = conn {
+            if c.write_all(payload.as_bytes()).is_err() {
+                conn = None;  // reconnect on the next batch
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    fn sample(name: &str) -> (String, BandwidthStats) {
+        (name.to_string(), BandwidthStats {
+            download_bps: 1234.5, upload_bps: 10.0, total_rx: 90000, total_tx: 8000,
+            rx_delta: 1500, tx_delta: 64, elapsed: 1.0, ..BandwidthStats::default()
+        })
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn endpoints_and_names() {
+        assert!(Pusher::start(Protocol::Statsd, "localhost", "p", "h").is_err());
+        assert!(Pusher::start(Protocol::Statsd, ":8125", "p", "h").is_err());
+        assert!(Pusher::start(Protocol::Statsd, "localhost:99999", "p", "h").is_err());
+        assert_eq!(sanitise("vEthernet (realtek).1"), "vEthernet__realtek__1");
+        assert_eq!(Pusher::start(Protocol::Statsd, "127.0.0.1:9", "", "web-1").unwrap().prefix, "web-1");
+        assert_eq!(Pusher::start(Protocol::Statsd, "127.0.0.1:9", "net.bw", "").unwrap().prefix, "net_bw");
+    }
+
+    #[test]
+    fn statsd_lines_arrive_as_datagrams() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let p = Pusher::start(Protocol::Statsd, &server.local_addr().unwrap().to_string(), "bw", "host.a").unwrap();
+        let quiet = ("lo".to_string(), BandwidthStats::default());
+        p.push(&[sample("eth0"), quiet]);
+
+        let mut buf = [0u8; 2048];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(std::str::from_utf8(&buf[..n]).unwrap(), "\
+bw.host_a.eth0.rx_bps:1234.50|g
+bw.host_a.eth0.tx_bps:10.00|g
+bw.host_a.eth0.rx_bytes:1500|c
+bw.host_a.eth0.tx_bytes:64|c");
+    }
+
+    #[test]
+    fn statsd_batches_split_below_the_mtu() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let p = Pusher::start(Protocol::Statsd, &server.local_addr().unwrap().to_string(), "bw", "h").unwrap();
+        let samples: Vec<_> = (0..40).map(|i| sample(&format!("veth{:02}", i))).collect();
+        p.push(&samples);
+
+        let mut lines = Vec::new();
+        let mut buf = [0u8; 2048];
+        while lines.len() < 160 {
+            let n = server.recv(&mut buf).unwrap();
+            assert!(n <= MAX_DATAGRAM, "{} byte datagram", n);
+            lines.extend(std::str::from_utf8(&buf[..n]).unwrap().lines().map(str::to_string));
+        }
+        assert_eq!(lines.len(), 160);
+        assert_eq!(lines[0], "bw.h.veth00.rx_bps:1234.50|g");
+        assert_eq!(lines[159], "bw.h.veth39.tx_bytes:64|c");
+    }
+
+    #[test]
+    fn graphite_lines_arrive_over_tcp() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let p = Pusher::start(Protocol::Graphite, &server.local_addr().unwrap().to_string(), "bw", "h").unwrap();
+        let before = now();
+        p.push(&[sample("eth0")]);
+
+        let (conn, _) = server.accept().unwrap();
+        conn.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let lines: Vec<String> = BufReader::new(conn).lines().take(4).map(Result::unwrap).collect();
+        let ts: u64 = lines[0].rsplit(' ').next().unwrap().parse().unwrap();
+        assert!(ts >= before && ts <= now());
+        assert_eq!(lines, [
+            format!("bw.h.eth0.rx_bps 1234.50 {}", ts),
+            format!("bw.h.eth0.tx_bps 10.00 {}", ts),
+            format!("bw.h.eth0.rx_bytes 90000 {}", ts),
+            format!("bw.h.eth0.tx_bytes 8000 {}", ts),
+        ]);
+    }
+}
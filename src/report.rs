@@ -0,0 +1,312 @@
+// File: src/report.rs
+//! `bandwidthmon report` — hourly / daily / monthly traffic tables built from
+//! the persistent history written by `--persist`.
+
+use crate::select::{self, Pattern, Target};
+use crate::store::{self, Record};
+use crate::{fmt_bps, fmt_total, styled, C_CYAN, C_GREEN, C_GREY, C_WHITE, C_YELLOW};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+// ── CLI ───────────────────────────────────────────────────────────────────────
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Period { Hourly, Daily, Monthly }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat { Table, Json, Csv }
+
+#[derive(clap::Args, Debug)]
+pub struct ReportArgs {
+    /// Only report these interfaces: a name, a glob such as 'eth*' or 're:REGEX', as for live -i
+    #[arg(short, long)]
+    pub iface: Option<String>,
+
+    /// Table granularity
+    #[arg(short, long, value_enum, default_value_t = Period::Daily)]
+    pub by: Period,
+
+    /// Also list the N busiest days per interface
+    #[arg(short, long)]
+    pub top: Option<usize>,
+
+    /// First day to include (YYYY-MM-DD, local time)
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+
+    /// Last day to include (YYYY-MM-DD, inclusive)
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = ReportFormat::Table)]
+    pub format: ReportFormat,
+}
+
+// ── Aggregation ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+struct Row {
+    period:      String,
+    interface:   String,
+    rx_bytes:    u64,
+    tx_bytes:    u64,
+    total_bytes: u64,
+    /// Mean rate over the seconds actually sampled in this bucket.
+    avg_bps:     f64,
+}
+
+#[derive(Serialize)]
+struct Report {
+    by:       &'static str,
+    rows:     Vec<Row>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_days: Option<Vec<Row>>,
+}
+
+fn period_name(p: Period) -> &'static str {
+    match p { Period::Hourly => "hourly", Period::Daily => "daily", Period::Monthly => "monthly" }
+}
+
+fn bucket_key(ts: i64, p: Period) -> String {
+    let t: DateTime<Local> = Local.timestamp_opt(ts, 0).single().unwrap_or_default();
+    let fmt = match p {
+        Period::Hourly  => "%Y-%m-%d %H:00",
+        Period::Daily   => "%Y-%m-%d",
+        Period::Monthly => "%Y-%m",
+    };
+    t.format(fmt).to_string()
+}
+
+/// Sum records into (interface, bucket) rows, sorted by interface then time.
+fn aggregate(records: &[Record], p: Period) -> Vec<Row> {
+    let mut acc: BTreeMap<(String, String), (u64, u64, f64)> = BTreeMap::new();
+    for r in records {
+        let e = acc.entry((r.interface.clone(), bucket_key(r.ts, p))).or_default();
+        e.0 += r.rx;
+        e.1 += r.tx;
+        e.2 += r.secs;
+    }
+    acc.into_iter()
+        .map(|((interface, period), (rx, tx, secs))| Row {
+            period, interface,
+            rx_bytes: rx, tx_bytes: tx, total_bytes: rx + tx,
+            avg_bps: if secs > 0.0 { (rx + tx) as f64 / secs } else { 0.0 },
+        })
+        .collect()
+}
+
+/// Busiest `n` days per interface, busiest first.
+fn top_days(records: &[Record], n: usize) -> Vec<Row> {
+    let mut days = aggregate(records, Period::Daily);
+    days.sort_by(|a, b| a.interface.cmp(&b.interface).then(b.total_bytes.cmp(&a.total_bytes)));
+    let mut out = Vec::new();
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    for d in days {
+        let c = seen.entry(d.interface.clone()).or_default();
+        if *c < n { *c += 1; out.push(d); }
+    }
+    out
+}
+
+/// Same matching rules as live `-i`, over the interfaces in the history: a
+/// glob or regex takes every match, an exact name wins, and a plain name
+/// that several interfaces contain is refused as ambiguous. `--exclude`
+/// drops interfaces with or without a pattern.
+fn filter_interface(records: Vec<Record>, pattern: Option<&str>, exclude: &[Pattern]) -> Result<Vec<Record>> {
+    let records: Vec<Record> = records.into_iter().filter(|r| !select::excluded(&r.interface, exclude)).collect();
+    let Some(pattern) = pattern else { return Ok(records) };
+    let names: Vec<String> = records.iter().map(|r| r.interface.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+    if names.is_empty() { return Ok(records); }
+    let keep: Vec<String> = match select::resolve(pattern, exclude, &names)? {
+        Target::Group(g) => names.into_iter().filter(|n| g.contains(n)).collect(),
+        t                => vec![t.name().to_string()],
+    };
+    Ok(records.into_iter().filter(|r| keep.contains(&r.interface)).collect())
+}
+
+fn local_midnight(d: NaiveDate) -> Result<i64> {
+    let t = d.and_hms_opt(0, 0, 0).context("Invalid date")?;
+    Local.from_local_datetime(&t).earliest()
+        .map(|t| t.timestamp())
+        .with_context(|| format!("Date {} does not exist in the local time zone", d))
+}
+
+// ── Rendering ─────────────────────────────────────────────────────────────────
+
+fn print_table(title: &str, head: &str, rows: &[&Row]) {
+    println!("\n{}", styled(&format!("═══ {} ═══", title), C_CYAN, true));
+    println!("  {}", styled(&format!("{:<16} {:>12} {:>12} {:>12} {:>14}",
+        head, "RX", "TX", "Total", "Avg rate"), C_WHITE, true));
+    println!("  {}", styled(&"─".repeat(70), C_GREY, false));
+    for r in rows {
+        println!("  {} {} {} {} {}",
+            styled(&format!("{:<16}", r.period), C_WHITE, false),
+            styled(&format!("{:>12}", fmt_total(r.rx_bytes)), C_CYAN, false),
+            styled(&format!("{:>12}", fmt_total(r.tx_bytes)), C_YELLOW, false),
+            styled(&format!("{:>12}", fmt_total(r.total_bytes)), C_WHITE, true),
+            styled(&format!("{:>14}", fmt_bps(r.avg_bps)), C_GREEN, false));
+    }
+}
+
+fn print_csv(section: &str, rows: &[Row]) {
+    for r in rows {
+        println!("{},{},{},{},{},{},{:.2}",
+            section, r.period, csv_field(&r.interface),
+            r.rx_bytes, r.tx_bytes, r.total_bytes, r.avg_bps);
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) { format!("\"{}\"", s.replace('"', "\"\"")) } else { s.to_string() }
+}
+
+// ── Entry ─────────────────────────────────────────────────────────────────────
+
+pub fn run(args: &ReportArgs, data_dir: &Path, exclude: &[Pattern]) -> Result<()> {
+    let from = args.from.map(local_midnight).transpose()?;
+    let to = args.to
+        .map(|d| d.succ_opt().context("Date out of range").and_then(local_midnight))
+        .transpose()?;
+
+    let records = filter_interface(store::load(data_dir, from, to)?, args.iface.as_deref(), exclude)?;
+
+    let rows = aggregate(&records, args.by);
+    let top  = args.top.map(|n| top_days(&records, n));
+
+    match args.format {
+        ReportFormat::Json => {
+            let report = Report { by: period_name(args.by), rows, top_days: top };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        ReportFormat::Csv => {
+            println!("section,period,interface,rx_bytes,tx_bytes,total_bytes,avg_bps");
+            print_csv(period_name(args.by), &rows);
+            if let Some(ref top) = top { print_csv("top", top); }
+        }
+        ReportFormat::Table => {
+            if rows.is_empty() {
+                println!("{}", styled(&format!("No history in {}", data_dir.display()), C_GREY, false));
+                return Ok(());
+            }
+            let head = match args.by { Period::Hourly => "Hour", Period::Daily => "Day", Period::Monthly => "Month" };
+            let mut ifaces: Vec<&str> = rows.iter().map(|r| r.interface.as_str()).collect();
+            ifaces.dedup();
+            for iface in ifaces {
+                let title = format!("{} traffic ({})", capitalise(period_name(args.by)), iface);
+                let mine: Vec<&Row> = rows.iter().filter(|r| r.interface == iface).collect();
+                print_table(&title, head, &mine);
+                if let Some(ref top) = top {
+                    let best: Vec<&Row> = top.iter().filter(|r| r.interface == iface).collect();
+                    print_table(&format!("Top {} days ({})", best.len(), iface), "Day", &best);
+                }
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn capitalise(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        Some(f) => f.to_uppercase().chain(c).collect(),
+        None    => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(ts: i64, interface: &str, rx: u64, tx: u64) -> Record {
+        Record { ts, interface: interface.to_string(), rx, tx, secs: 1.0 }
+    }
+
+    /// Local noon on a day, so buckets don't depend on the test's time zone.
+    fn day(m: u32, d: u32) -> i64 {
+        Local.with_ymd_and_hms(2026, m, d, 12, 0, 0).unwrap().timestamp()
+    }
+
+    fn summary(rows: &[Row]) -> Vec<(&str, &str, u64, u64)> {
+        rows.iter().map(|r| (r.period.as_str(), r.interface.as_str(), r.rx_bytes, r.tx_bytes)).collect()
+    }
+
+    fn filtered(pattern: Option<&str>, exclude: &[&str]) -> Result<Vec<String>> {
+        let records = ["eth0", "eth1", "veth1a2b", "wlan0"].iter().map(|i| rec(0, i, 1, 1)).collect();
+        let exclude: Vec<Pattern> = exclude.iter().map(|e| Pattern::parse(e).unwrap()).collect();
+        Ok(filter_interface(records, pattern, &exclude)?.into_iter().map(|r| r.interface).collect())
+    }
+
+    #[test]
+    fn aggregate_sums_per_interface_and_bucket() {
+        let mut records = vec![
+            rec(day(10, 1), "eth0", 100, 10),
+            rec(day(10, 1) + 60, "eth0", 300, 30),
+            rec(day(10, 2), "eth0", 50, 5),
+            rec(day(10, 1), "wlan0", 7, 0),
+            rec(day(11, 3), "eth0", 1, 1),
+        ];
+        records[1].secs = 3.0;
+
+        let daily = aggregate(&records, Period::Daily);
+        assert_eq!(summary(&daily), [
+            ("2026-10-01", "eth0", 400, 40),
+            ("2026-10-02", "eth0", 50, 5),
+            ("2026-11-03", "eth0", 1, 1),
+            ("2026-10-01", "wlan0", 7, 0),
+        ]);
+        assert_eq!(daily[0].total_bytes, 440);
+        assert_eq!(daily[0].avg_bps, 110.0);   // 440 bytes over 4 sampled seconds
+
+        let monthly = aggregate(&records, Period::Monthly);
+        assert_eq!(summary(&monthly), [("2026-10", "eth0", 450, 45), ("2026-11", "eth0", 1, 1), ("2026-10", "wlan0", 7, 0)]);
+        let hourly = aggregate(&records, Period::Hourly);
+        assert_eq!(hourly[0].period, "2026-10-01 12:00");
+        assert_eq!(hourly[0].rx_bytes, 400);
+
+        records[3].secs = 0.0;
+        assert_eq!(aggregate(&records[3..4], Period::Daily)[0].avg_bps, 0.0);
+        assert!(aggregate(&[], Period::Daily).is_empty());
+    }
+
+    #[test]
+    fn top_days_are_per_interface_busiest_first() {
+        let records = vec![
+            rec(day(10, 1), "eth0", 10, 0),
+            rec(day(10, 2), "eth0", 30, 0),
+            rec(day(10, 3), "eth0", 20, 0),
+            rec(day(10, 3), "eth0", 15, 0),
+            rec(day(10, 1), "wlan0", 5, 0),
+        ];
+        assert_eq!(summary(&top_days(&records, 2)), [
+            ("2026-10-03", "eth0", 35, 0),
+            ("2026-10-02", "eth0", 30, 0),
+            ("2026-10-01", "wlan0", 5, 0),
+        ]);
+        assert!(top_days(&records, 0).is_empty());
+        assert_eq!(top_days(&records, 10).len(), 4);
+    }
+
+    #[test]
+    fn interface_filter_matches_like_live_i() {
+        assert_eq!(filtered(None, &[]).unwrap(), ["eth0", "eth1", "veth1a2b", "wlan0"]);
+        assert_eq!(filtered(Some("eth0"), &[]).unwrap(), ["eth0"]);
+        assert_eq!(filtered(Some("wlan"), &[]).unwrap(), ["wlan0"]);
+        assert_eq!(filtered(Some("eth*"), &[]).unwrap(), ["eth0", "eth1"]);
+        assert_eq!(filtered(Some("re:^v?eth1"), &[]).unwrap(), ["eth1", "veth1a2b"]);
+        assert_eq!(filtered(Some("eth*"), &["eth1"]).unwrap(), ["eth0"]);
+        assert_eq!(filtered(None, &["*eth*"]).unwrap(), ["wlan0"]);
+        // A substring of several interfaces is refused, not summed.
+        let err = filtered(Some("eth"), &[]).unwrap_err();
+        assert!(err.downcast_ref::<select::Ambiguous>().is_some());
+        // ...unless the exclusions leave only one.
+        assert_eq!(filtered(Some("eth"), &["eth1", "veth*"]).unwrap(), ["eth0"]);
+        assert!(filtered(Some("ppp0"), &[]).is_err());
+        assert!(filter_interface(Vec::new(), Some("eth0"), &[]).unwrap().is_empty());
+    }
+}
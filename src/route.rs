@@ -0,0 +1,130 @@
+// File: src/route.rs
+//! Default routes, for choosing an interface when `-i` is not given.
+//!
+//! The interface the default route leaves through carries the host's
+//! traffic; the one with the most bytes since boot is often `lo` or a docker
+//! bridge instead. Linux lists routes in `/proc/net/route` (IPv4 in hex, in
+//! host byte order) and `/proc/net/ipv6_route`; elsewhere neither exists and
+//! the caller measures instead.
+
+use crate::netns::{self, Namespace};
+use std::fs::File;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const RTF_UP:     u32 = 0x0001;
+const RTF_REJECT: u32 = 0x0200;
+
+#[derive(Clone, Debug)]
+pub struct DefaultRoute {
+    pub interface: String,
+    pub gateway:   Option<IpAddr>,
+    pub metric:    u32,
+}
+
+/// Usable default routes of `ns` (or ours), lowest metric first; IPv4
+/// before IPv6 at equal metrics. Empty when the tables can't be read.
+pub fn defaults(ns: Option<&Namespace>) -> Vec<DefaultRoute> {
+    let mut routes = parse_v4(&read(ns, "route"));
+    routes.extend(parse_v6(&read(ns, "ipv6_route")));
+    routes.sort_by_key(|r| r.metric);
+    routes
+}
+
+fn read(ns: Option<&Namespace>, file: &str) -> String {
+    let f = match ns {
+        Some(ns) => netns::open_net(ns, file).ok(),
+        None     => File::open(format!("/proc/net/{}", file)).ok(),
+    };
+    let mut text = String::new();
+    if let Some(mut f) = f { let _ = f.read_to_string(&mut text); }
+    text
+}
+
+/// `Iface Destination Gateway Flags RefCnt Use Metric Mask …` after a header.
+fn parse_v4(text: &str) -> Vec<DefaultRoute> {
+    text.lines().skip(1).filter_map(|line| {
+        let f: Vec<&str> = line.split_whitespace().collect();
+        if f.len() < 8 || f[1] != "00000000" || f[7] != "00000000" { return None; }
+        let flags = u32::from_str_radix(f[3], 16).ok()?;
+        if flags & RTF_UP == 0 || flags & RTF_REJECT != 0 { return None; }
+        let gw = u32::from_str_radix(f[2], 16).ok()?;
+        Some(DefaultRoute {
+            interface: f[0].to_string(),
+            gateway:   (gw != 0).then(|| IpAddr::V4(Ipv4Addr::from(gw.to_ne_bytes()))),
+            metric:    f[6].parse().ok()?,
+        })
+    }).collect()
+}
+
+/// `dest dest_len src src_len next_hop metric refcnt use flags iface`, all
+/// hex; the kernel's "unreachable" default on `lo` is a reject route.
+fn parse_v6(text: &str) -> Vec<DefaultRoute> {
+    text.lines().filter_map(|line| {
+        let f: Vec<&str> = line.split_whitespace().collect();
+        if f.len() < 10 || u128::from_str_radix(f[0], 16).ok()? != 0 || f[1] != "00" { return None; }
+        let flags = u32::from_str_radix(f[8], 16).ok()?;
+        if flags & RTF_UP == 0 || flags & RTF_REJECT != 0 || f[9] == "lo" { return None; }
+        let gw = u128::from_str_radix(f[4], 16).ok()?;
+        Some(DefaultRoute {
+            interface: f[9].to_string(),
+            gateway:   (gw != 0).then(|| IpAddr::V6(Ipv6Addr::from(gw))),
+            metric:    u32::from_str_radix(f[5], 16).ok()?,
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `/proc/net/route` with the gateway 192.0.2.1 as this host writes it.
+    fn route_table() -> String {
+        let gw = format!("{:08X}", u32::from_ne_bytes([192, 0, 2, 1]));
+        format!("\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t00000000\t{gw}\t0003\t0\t0\t100\t00000000\t0\t0\t0
+wlan0\t00000000\t{gw}\t0003\t0\t0\t600\t00000000\t0\t0\t0
+eth0\t000200C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+tun0\t00000000\t00000000\t0001\t0\t0\t50\t00000000\t0\t0\t0
+eth1\t00000000\t{gw}\t0203\t0\t0\t10\t00000000\t0\t0\t0
+eth2\t00000000\t{gw}\t0002\t0\t0\t10\t00000000\t0\t0\t0
+eth3\t00000000\t{gw}\t0003\t0\t0\t10\t00000080\t0\t0\t0
+")
+    }
+
+    const IPV6_ROUTE: &str = "\
+fe800000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000001 00000000 00000001      wg0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+20010db8000000000000000000000000 20 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000201     eth1
+";
+
+    fn summary(routes: &[DefaultRoute]) -> Vec<(&str, Option<IpAddr>, u32)> {
+        routes.iter().map(|r| (r.interface.as_str(), r.gateway, r.metric)).collect()
+    }
+
+    #[test]
+    fn ipv4_defaults() {
+        let gw = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+        // Not the subnet route, not a reject (eth1), down (eth2) or non-zero mask (eth3) one.
+        assert_eq!(summary(&parse_v4(&route_table())), [("eth0", gw, 100), ("wlan0", gw, 600), ("tun0", None, 50)]);
+    }
+
+    #[test]
+    fn ipv6_defaults() {
+        let gw = "fe80::1".parse().ok();
+        // lo's "unreachable" default and eth1's reject route are left out; metrics are hex.
+        assert_eq!(summary(&parse_v6(IPV6_ROUTE)), [("eth0", gw, 1024), ("wg0", None, 0)]);
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        assert!(parse_v4("").is_empty());
+        assert!(parse_v4("Iface\tDestination\neth0\t00000000\tZZ\t0003\t0\t0\t0\t00000000").is_empty());
+        assert!(parse_v4("header\neth0 00000000 00000000 0003 0 0 x 00000000").is_empty());
+        assert!(parse_v6("00 00 00").is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+// File: src/sampler.rs
+//! Counter sampling shared by the TUI and the headless recorder.
+//!
+//! One `Sampler` owns the sysinfo `Networks` handle and the previous counter
+//! snapshot of every interface, so a single refresh yields rates for all of
+//! them. The TUI picks its interface out of the result; `record` stores all.
+//! With `--pid` / `--netns` the counters come from another network
+//! namespace's `net/dev` instead (see `netns`).
+
+use crate::iface::{self, Details};
+use crate::netns::{Namespace, NetDev};
+use crate::select::Device;
+use crate::BandwidthStats;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Instant;
+use sysinfo::Networks;
+
+/// Cumulative counters of one interface.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Totals {
+    pub rx:         u64,
+    pub tx:         u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors:  u64,
+    pub tx_errors:  u64,
+}
+
+enum Source {
+    Host(Networks),
+    Namespace(NetDev),
+}
+
+pub struct Sampler {
+    source:    Source,
+    prev:      HashMap<String, (u64, u64)>,
+    prev_time: Instant,
+}
+
+impl Sampler {
+    pub fn new() -> Self {
+        Self::with_source(Source::Host(Networks::new_with_refreshed_list()))
+    }
+
+    /// Counters of `ns`, or of our own namespace.
+    pub fn open(ns: Option<&Namespace>) -> Result<Self> {
+        match ns {
+            Some(ns) => Ok(Self::with_source(Source::Namespace(NetDev::open(ns)?))),
+            None     => Ok(Self::new()),
+        }
+    }
+
+    fn with_source(source: Source) -> Self {
+        let mut s = Self { source, prev: HashMap::new(), prev_time: Instant::now() };
+        s.prev = s.totals().into_iter().map(|(n, t)| (n, (t.rx, t.tx))).collect();
+        s
+    }
+
+    pub fn contains(&self, interface: &str) -> bool {
+        self.prev.contains_key(interface)
+    }
+
+    /// Addresses assigned to `interface` as of the last refresh (none inside
+    /// another namespace).
+    pub fn addresses(&self, interface: &str) -> Vec<IpAddr> {
+        match self.source {
+            Source::Host(ref nets) => nets.get(interface)
+                .map(|d| d.ip_networks().iter().map(|n| n.addr).collect())
+                .unwrap_or_default(),
+            Source::Namespace(_) => Vec::new(),
+        }
+    }
+
+    /// The current name of `device` as of the last refresh (never inside
+    /// another namespace). A MAC that a bridge, bond or VLAN shares with its
+    /// port belongs to the port.
+    pub fn find(&self, device: &Device) -> Option<String> {
+        let Source::Host(ref nets) = self.source else { return None };
+        if let Device::Altname(alt) = device {
+            return iface::canonical_name(alt).filter(|n| nets.contains_key(n));
+        }
+        let mut names: Vec<&String> = nets.iter().filter(|(_, d)| match device {
+            Device::Mac(mac)   => d.mac_address().0 == *mac,
+            Device::Ip(ip)     => d.ip_networks().iter().any(|n| n.addr == *ip),
+            Device::Altname(_) => false,
+        }).map(|(n, _)| n).collect();
+        names.sort();
+        if names.len() > 1 {
+            if let Some(port) = names.iter().find(|n| !iface::borrows_mac(n)) { return Some(port.to_string()); }
+        }
+        names.first().map(|n| n.to_string())
+    }
+
+    /// What `--list` shows about `interface` (nothing but its name inside
+    /// another namespace).
+    pub fn details(&self, interface: &str) -> Details {
+        match self.source {
+            Source::Host(ref nets) => Details::of(interface, nets.get(interface)),
+            Source::Namespace(_)   => Details::default(),
+        }
+    }
+
+    /// Refresh and return the counters of every interface, sorted by name.
+    /// A namespace that went away has no interfaces.
+    pub fn totals(&mut self) -> Vec<(String, Totals)> {
+        let mut out = match self.source {
+            Source::Host(ref mut nets) => {
+                nets.refresh(true);
+                nets.iter().map(|(name, d)| (name.clone(), Totals {
+                    rx:         d.total_received(),          tx:         d.total_transmitted(),
+                    rx_packets: d.total_packets_received(),  tx_packets: d.total_packets_transmitted(),
+                    rx_errors:  d.total_errors_on_received(), tx_errors: d.total_errors_on_transmitted(),
+                })).collect()
+            }
+            Source::Namespace(ref mut dev) => dev.read().unwrap_or_default(),
+        };
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    /// Refresh counters and return one entry per interface, sorted by name.
+    ///
+    /// Interfaces that appeared since the last call report zero for this
+    /// interval (their counters become the baseline). Counter resets — a
+    /// driver reload or a 32-bit wrap — saturate to zero instead of
+    /// producing a huge bogus spike.
+    pub fn sample(&mut self) -> Vec<(String, BandwidthStats)> {
+        let totals = self.totals();
+        let cur_time = Instant::now();
+        let elapsed  = cur_time.duration_since(self.prev_time).as_secs_f64();
+        let settled  = elapsed >= 0.001;
+
+        let out: Vec<(String, BandwidthStats)> = totals.into_iter()
+            .map(|(name, t)| {
+                let (cur_rx, cur_tx) = (t.rx, t.tx);
+                let (prev_rx, prev_tx) = self.prev.get(&name).copied().unwrap_or((cur_rx, cur_tx));
+                let (rx_delta, tx_delta) = if settled {
+                    (cur_rx.saturating_sub(prev_rx), cur_tx.saturating_sub(prev_tx))
+                } else {
+                    (0, 0)
+                };
+                let rate = |d: u64| if settled { d as f64 / elapsed } else { 0.0 };
+                (name, BandwidthStats {
+                    download_bps: rate(rx_delta), upload_bps: rate(tx_delta),
+                    total_rx: cur_rx,             total_tx:   cur_tx,
+                    rx_delta,                     tx_delta,
+                    elapsed,
+                    rx_packets: t.rx_packets,     tx_packets: t.tx_packets,
+                    rx_errors:  t.rx_errors,      tx_errors:  t.tx_errors,
+                })
+            })
+            .collect();
+
+        // Too soon to measure: keep the old baseline so the next call sees
+        // the full interval.
+        if settled {
+            self.prev = out.iter().map(|(n, s)| (n.clone(), (s.total_rx, s.total_tx))).collect();
+            self.prev_time = cur_time;
+        }
+        out
+    }
+}
@@ -0,0 +1,382 @@
+// File: src/select.rs
+//! Interface selection: `-i` and `--exclude`.
+//!
+//!   -i eth0                exactly eth0, else the one interface containing "eth0"
+//!   -i 'eth*'              every interface matching the glob (`*`, `?`, `[…]`)
+//!   -i 're:^(en|wl)'       every interface matching the regex
+//!   --exclude 'docker*'    never these (repeatable; also applies to --list)
+//!
+//! A glob or regex selects a group: the charts show its members added up,
+//! re-matched on every sample so interfaces that appear later join in. The
+//! group sits in the picker under the pattern's own text, next to its
+//! members. A plain name that several interfaces contain is refused rather
+//! than guessed; the live view opens the picker on those instead.
+//!
+//!   -i mac:52:54:00:12:34:56   the interface with this MAC
+//!   -i ip:192.168.1.10         the interface with this address
+//!   -i wan                     a kernel altname (`ip link property add … altname wan`)
+//!
+//! These name a device rather than an interface name, which udev may change
+//! between reboots or replugs. The monitor looks the device up again on
+//! every sample and follows it to its new name; the history store keeps it
+//! under the identifier, so it stays one series.
+
+use crate::BandwidthStats;
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::fmt;
+use std::net::IpAddr;
+
+/// One `-i` / `--exclude` value.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Name(String),
+    Glob(String, Regex),
+    Regex(String, Regex),
+}
+
+impl Pattern {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if let Some(re) = s.strip_prefix("re:") {
+            let r = Regex::new(re).map_err(|e| format!("invalid regex '{}': {}", re, e))?;
+            return Ok(Pattern::Regex(s.to_string(), r));
+        }
+        if s.contains(['*', '?', '[']) { return Ok(Pattern::Glob(s.to_string(), glob(s)?)); }
+        if s.trim().is_empty() { return Err("empty interface name".to_string()); }
+        Ok(Pattern::Name(s.to_string()))
+    }
+
+    /// Globs and regexes select every match, not one interface.
+    pub fn is_group(&self) -> bool {
+        !matches!(self, Pattern::Name(_))
+    }
+
+    /// The exact name, or a glob / regex match.
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Pattern::Name(n)                           => n == name,
+            Pattern::Glob(_, r) | Pattern::Regex(_, r) => r.is_match(name),
+        }
+    }
+
+    /// Like `matches`, but a plain name also takes every interface that
+    /// contains it, in any case — for filters such as `--list -i`.
+    pub fn selects(&self, name: &str) -> bool {
+        match self {
+            Pattern::Name(n) => name.to_lowercase().contains(&n.to_lowercase()),
+            _                => self.matches(name),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self { Pattern::Name(s) | Pattern::Glob(s, _) | Pattern::Regex(s, _) => f.write_str(s) }
+    }
+}
+
+/// `eth[0-3]*` → `^eth[0-3].*$`; `[!…]` negates as in the shell, and a `]`
+/// right after the opening `[` (or `[!`) is a literal.
+fn glob(s: &str) -> Result<Regex, String> {
+    let mut re = String::from("^");
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            '[' => {
+                re.push('[');
+                if chars.next_if_eq(&'!').is_some() { re.push('^'); }
+                let mut class: Vec<char> = chars.next_if_eq(&']').into_iter().collect();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(e)   => class.push(e),
+                        None      => return Err(format!("unclosed '[' in '{}'", s)),
+                    }
+                }
+                push_class(&mut re, &class);
+                re.push(']');
+            }
+            c => re.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    re.push('$');
+    Regex::new(&re).map_err(|e| format!("invalid pattern '{}': {}", s, e))
+}
+
+/// A glob class body as regex: `a-z` stays a range, everything else is a
+/// literal — including `&&`, `--` and `~~`, which regex classes treat as
+/// set operators.
+fn push_class(re: &mut String, class: &[char]) {
+    let literal = |re: &mut String, c: char| {
+        if matches!(c, '\\' | '[' | ']' | '^' | '-' | '&' | '~') { re.push('\\'); }
+        re.push(c);
+    };
+    let mut i = 0;
+    while i < class.len() {
+        literal(re, class[i]);
+        if class.get(i + 1) == Some(&'-') && i + 2 < class.len() {
+            re.push('-');
+            literal(re, class[i + 2]);
+            i += 3;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Whether any `--exclude` pattern matches `name`.
+pub fn excluded(name: &str, exclude: &[Pattern]) -> bool {
+    exclude.iter().any(|p| p.matches(name))
+}
+
+// ── Devices ───────────────────────────────────────────────────────────────────
+
+/// One device, by something other than its current name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Device {
+    Mac([u8; 6]),
+    Ip(IpAddr),
+    Altname(String),
+}
+
+impl Device {
+    /// `mac:…` or `ip:…`; `None` for anything else.
+    pub fn parse(s: &str) -> Option<Result<Self, String>> {
+        if let Some(m) = s.strip_prefix("mac:") { return Some(parse_mac(m).map(Device::Mac)); }
+        let a = s.strip_prefix("ip:")?;
+        Some(a.parse().map(Device::Ip).map_err(|_| format!("invalid IP address '{}'", a)))
+    }
+
+    /// For the startup line: `MAC 52:54:00:12:34:56`.
+    pub fn describe(&self) -> String {
+        match self {
+            Device::Mac(_)     => format!("MAC {}", &self.to_string()[4..]),
+            Device::Ip(ip)     => format!("address {}", ip),
+            Device::Altname(n) => format!("altname {}", n),
+        }
+    }
+}
+
+/// The identifier as typed; the history store's key for the device.
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Device::Mac(m)     => write!(f, "mac:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", m[0], m[1], m[2], m[3], m[4], m[5]),
+            Device::Ip(ip)     => write!(f, "ip:{}", ip),
+            Device::Altname(n) => f.write_str(n),
+        }
+    }
+}
+
+/// `52:54:00:12:34:56` or `52-54-00-12-34-56`.
+fn parse_mac(s: &str) -> Result<[u8; 6], String> {
+    let bad = || format!("invalid MAC address '{}'", s);
+    let parts: Vec<&str> = s.split([':', '-']).collect();
+    if parts.len() != 6 { return Err(bad()); }
+    let mut mac = [0u8; 6];
+    for (b, p) in mac.iter_mut().zip(&parts) {
+        if p.is_empty() || p.len() > 2 { return Err(bad()); }
+        *b = u8::from_str_radix(p, 16).map_err(|_| bad())?;
+    }
+    Ok(mac)
+}
+
+// ── Groups ────────────────────────────────────────────────────────────────────
+
+/// Every interface matching a glob or regex, summed under the pattern's text.
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub name: String,
+    include:  Pattern,
+    exclude:  Vec<Pattern>,
+}
+
+impl Group {
+    pub fn contains(&self, name: &str) -> bool {
+        name != self.name && self.include.matches(name) && !excluded(name, &self.exclude)
+    }
+
+    /// One sample of the whole group: rates, deltas and counters added up.
+    pub fn sum(&self, samples: &[(String, BandwidthStats)]) -> BandwidthStats {
+        let elapsed = samples.first().map(|(_, s)| s.elapsed).unwrap_or_default();
+        samples.iter().filter(|(n, _)| self.contains(n)).fold(
+            BandwidthStats { elapsed, ..BandwidthStats::default() },
+            |mut t, (_, s)| {
+                t.download_bps += s.download_bps; t.upload_bps += s.upload_bps;
+                t.total_rx     += s.total_rx;     t.total_tx   += s.total_tx;
+                t.rx_delta     += s.rx_delta;     t.tx_delta   += s.tx_delta;
+                t.rx_packets   += s.rx_packets;   t.tx_packets += s.tx_packets;
+                t.rx_errors    += s.rx_errors;    t.tx_errors  += s.tx_errors;
+                t
+            })
+    }
+}
+
+// ── Resolving -i ──────────────────────────────────────────────────────────────
+
+/// What `-i` resolved to.
+#[derive(Clone, Debug)]
+pub enum Target {
+    Interface(String),
+    Group(Group),
+    Device(Device, String),   // and its current name
+}
+
+impl Target {
+    pub fn name(&self) -> &str {
+        match self { Target::Interface(n) | Target::Device(_, n) => n, Target::Group(g) => &g.name }
+    }
+}
+
+/// A plain `-i` contained in several interface names.
+#[derive(Debug)]
+pub struct Ambiguous {
+    pub pattern: String,
+    pub matches: Vec<String>,
+}
+
+impl fmt::Display for Ambiguous {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let all_prefixed = self.matches.iter().all(|m| m.starts_with(&self.pattern));
+        write!(f, "'{}' matches several interfaces: {}\nGive the full name, or -i '{}{}*' to watch them together",
+            self.pattern, self.matches.join(", "), if all_prefixed { "" } else { "*" }, self.pattern)
+    }
+}
+
+impl std::error::Error for Ambiguous {}
+
+/// Resolve `-i pattern` among `names`, leaving out `exclude`. An exact name
+/// wins; otherwise a plain name must be contained in exactly one interface,
+/// and an `Ambiguous` error lists them all if not.
+pub fn resolve(pattern: &str, exclude: &[Pattern], names: &[String]) -> Result<Target> {
+    let p = Pattern::parse(pattern).map_err(anyhow::Error::msg)?;
+    let candidates: Vec<&str> = names.iter().map(String::as_str).filter(|n| !excluded(n, exclude)).collect();
+    let none = || anyhow::anyhow!("No interface matches '{}'. Available:\n{}", pattern, candidates.join("\n"));
+
+    if p.is_group() {
+        let group = Group { name: pattern.to_string(), include: p, exclude: exclude.to_vec() };
+        if !candidates.iter().any(|n| group.contains(n)) { return Err(none()); }
+        return Ok(Target::Group(group));
+    }
+    if candidates.contains(&pattern) { return Ok(Target::Interface(pattern.to_string())); }
+    let mut matches: Vec<String> = candidates.iter().filter(|n| p.selects(n)).map(|n| n.to_string()).collect();
+    match matches.len() {
+        0 => Err(none()),
+        1 => Ok(Target::Interface(matches.remove(0))),
+        _ => bail!(Ambiguous { pattern: pattern.to_string(), matches }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> { list.iter().map(|s| s.to_string()).collect() }
+
+    fn glob_matches(pattern: &str, name: &str) -> bool { glob(pattern).unwrap().is_match(name) }
+
+    const NAMES: &[&str] = &["docker0", "eth0", "eth1", "lo", "veth1a2b", "wlan0"];
+
+    #[test]
+    fn patterns() {
+        assert!(matches!(Pattern::parse("eth0"), Ok(Pattern::Name(n)) if n == "eth0"));
+        assert!(matches!(Pattern::parse("eth*"), Ok(Pattern::Glob(..))));
+        assert!(matches!(Pattern::parse("wl?n0"), Ok(Pattern::Glob(..))));
+        assert!(matches!(Pattern::parse("re:^(en|wl)"), Ok(Pattern::Regex(..))));
+        assert!(Pattern::parse("re:(").is_err());
+        assert!(Pattern::parse("eth[0").is_err());
+        assert!(Pattern::parse(" ").is_err());
+        assert_eq!(Pattern::parse("re:^e").unwrap().to_string(), "re:^e");
+
+        let p = Pattern::parse("ETH").unwrap();
+        assert!(!p.is_group() && !p.matches("eth0") && p.selects("eth0"));
+        let p = Pattern::parse("eth*").unwrap();
+        assert!(p.is_group() && p.matches("eth0") && !p.matches("veth0"));
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob_matches("eth*", "eth0"));
+        assert!(!glob_matches("eth*", "veth0"));
+        assert!(glob_matches("eth?", "eth1"));
+        assert!(!glob_matches("eth?", "eth10"));
+        assert!(glob_matches("eth[0-3]", "eth2"));
+        assert!(!glob_matches("eth[0-3]", "eth4"));
+        assert!(glob_matches("eth[!0-3]", "eth4"));
+        assert!(!glob_matches("eth[!0-3]", "eth2"));
+        assert!(glob_matches("br.[ab]", "br.a"));
+        assert!(!glob_matches("br.[ab]", "br-a"));
+    }
+
+    #[test]
+    fn glob_class_literals() {
+        // A leading ']' is part of the class.
+        assert!(glob_matches("x[]a]", "x]"));
+        assert!(glob_matches("x[]a]", "xa"));
+        assert!(glob_matches("x[!]]", "xb"));
+        assert!(!glob_matches("x[!]]", "x]"));
+        // Regex class operators are plain characters in a glob.
+        for (pattern, hit, miss) in [("x[&&a]", "x&", "xb"), ("x[--]", "x-", "xb"), ("x[~~b]", "x~", "xc"),
+                                     ("x[-a]", "x-", "xb"), ("x[a-]", "x-", "xb"), ("x[[]", "x[", "x\\"),
+                                     ("x[^a]", "x^", "xb")] {
+            assert!(glob_matches(pattern, hit), "{} should match {}", pattern, hit);
+            assert!(!glob_matches(pattern, miss), "{} should not match {}", pattern, miss);
+        }
+        assert!(glob("x[]").is_err());
+        assert!(glob("x[z-a]").is_err());
+    }
+
+    #[test]
+    fn resolve_exact_and_unique_names() {
+        let all = names(NAMES);
+        assert_eq!(resolve("eth0", &[], &all).unwrap().name(), "eth0");
+        assert_eq!(resolve("wlan", &[], &all).unwrap().name(), "wlan0");
+        assert_eq!(resolve("DOCK", &[], &all).unwrap().name(), "docker0");
+        assert!(resolve("ppp", &[], &all).is_err());
+    }
+
+    #[test]
+    fn resolve_refuses_ambiguous_names() {
+        let all = names(NAMES);
+        let err = resolve("eth", &[], &all).unwrap_err();
+        let amb = err.downcast_ref::<Ambiguous>().expect("an Ambiguous error");
+        assert_eq!(amb.matches, ["eth0", "eth1", "veth1a2b"]);
+        assert!(amb.to_string().contains("-i '*eth*'"));
+
+        // Excluding the others leaves one.
+        let exclude = [Pattern::parse("veth*").unwrap(), Pattern::parse("eth1").unwrap()];
+        assert_eq!(resolve("eth", &exclude, &all).unwrap().name(), "eth0");
+    }
+
+    #[test]
+    fn resolve_leaves_out_excluded() {
+        let all = names(NAMES);
+        let exclude = [Pattern::parse("docker*").unwrap()];
+        assert!(resolve("docker0", &exclude, &all).is_err());
+        assert!(resolve("dock", &exclude, &all).is_err());
+    }
+
+    #[test]
+    fn resolve_groups() {
+        let all = names(NAMES);
+        let exclude = [Pattern::parse("eth1").unwrap()];
+        let Target::Group(g) = resolve("re:eth", &exclude, &all).unwrap() else { panic!("not a group") };
+        assert_eq!(g.name, "re:eth");
+        let members: Vec<&String> = all.iter().filter(|n| g.contains(n)).collect();
+        assert_eq!(members, ["eth0", "veth1a2b"]);
+        assert!(resolve("ppp*", &[], &all).is_err());
+    }
+
+    #[test]
+    fn devices() {
+        assert_eq!(Device::parse("mac:52:54:00:AB:cd:0e"), Some(Ok(Device::Mac([0x52, 0x54, 0, 0xab, 0xcd, 0x0e]))));
+        assert_eq!(Device::parse("mac:52-54-00-ab-cd-0e").unwrap().unwrap().to_string(), "mac:52:54:00:ab:cd:0e");
+        assert!(Device::parse("mac:52:54:00:ab:cd").unwrap().is_err());
+        assert!(Device::parse("mac:52:54:00:ab:cd:100").unwrap().is_err());
+        assert_eq!(Device::parse("ip:10.0.0.1"), Some(Ok(Device::Ip("10.0.0.1".parse().unwrap()))));
+        assert!(Device::parse("ip:10.0.0").unwrap().is_err());
+        assert_eq!(Device::parse("eth0"), None);
+    }
+}
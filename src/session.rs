@@ -0,0 +1,406 @@
+// File: src/session.rs
+//! Session files (`--record FILE`) and `bandwidthmon replay FILE`.
+//!
+//! A session is JSON Lines: one header line naming the interface, then one
+//! line per interval with the full `BandwidthStats` and a millisecond
+//! timestamp:
+//!
+//!   {"format":"bandwidthmon-session","version":1,"interface":"eth0","started":"2026-10-18T09:00:00+02:00"}
+//!   {"t":1760770801000,"download_bps":1234.5,"upload_bps":42.0,"total_rx":...}
+//!
+//! Each line is flushed as it is written, so a session cut short by a crash
+//! still replays up to its last complete line.
+//!
+//! Replay feeds the recorded samples through the same `NetworkMonitor` and
+//! `render_frame` as the live view, so charts, peaks and averages look
+//! exactly as they did. Time is virtual: `--speed` and the `+`/`-` keys scale
+//! it, and seeking backwards rebuilds the monitor from the first sample.
+
+use crate::view::Inspect;
+use crate::{render_frame, styled, Args, BandwidthStats, NetworkMonitor, C_GREEN};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyModifiers},
+    terminal::{disable_raw_mode, enable_raw_mode},
+};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const FORMAT:  &str = "bandwidthmon-session";
+const VERSION: u32  = 1;
+
+/// Samples skipped by ←/→ and PgUp/PgDn.
+const SEEK_SHORT: usize = 10;
+const SEEK_LONG:  usize = 60;
+
+const MIN_SPEED: f64 = 0.125;
+const MAX_SPEED: f64 = 64.0;
+
+// ── CLI ───────────────────────────────────────────────────────────────────────
+
+#[derive(clap::Args, Debug)]
+pub struct ReplayArgs {
+    /// Session file written with --record
+    pub file: PathBuf,
+
+    /// Playback speed multiplier (2 = twice as fast)
+    #[arg(long, default_value_t = 1.0)]
+    pub speed: f64,
+}
+
+// ── File format ───────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format:    String,
+    version:   u32,
+    interface: String,
+    started:   String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Frame {
+    t: i64,  // unix milliseconds at the end of the interval
+    #[serde(flatten)]
+    stats: BandwidthStats,
+}
+
+/// Records the interface it was opened for, even if the view switches away.
+pub struct SessionWriter {
+    out:       BufWriter<File>,
+    interface: String,
+}
+
+impl SessionWriter {
+    /// Create (or truncate) `path` and write the header line.
+    pub fn create(path: &Path, interface: &str) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Cannot create session file {}", path.display()))?;
+        let mut w = Self { out: BufWriter::new(file), interface: interface.to_string() };
+        w.line(&Header {
+            format:    FORMAT.to_string(),
+            version:   VERSION,
+            interface: interface.to_string(),
+            started:   Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        })?;
+        Ok(w)
+    }
+
+    /// Append this writer's interface from one interval's samples.
+    pub fn write(&mut self, samples: &[(String, BandwidthStats)]) -> Result<()> {
+        let Some((_, stats)) = samples.iter().find(|(n, _)| n == &self.interface) else { return Ok(()) };
+        if stats.elapsed < 0.001 { return Ok(()); }  // nothing to play back
+        self.line(&Frame { t: Local::now().timestamp_millis(), stats: stats.clone() })
+    }
+
+    /// Keep recording a device that was renamed (`-i mac:…`).
+    pub fn rename(&mut self, from: &str, to: &str) {
+        if self.interface == from { self.interface = to.to_string(); }
+    }
+
+    fn line<T: Serialize>(&mut self, v: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.out, v)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Header and frames of a session file. A torn last line is ignored.
+fn load(path: &Path) -> Result<(Header, Vec<Frame>)> {
+    let file = File::open(path)
+        .with_context(|| format!("Cannot open session file {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines();
+
+    let first = lines.next().transpose()?.unwrap_or_default();
+    let header: Header = serde_json::from_str(&first)
+        .ok()
+        .filter(|h: &Header| h.format == FORMAT)
+        .with_context(|| format!("{} is not a bandwidthmon session file", path.display()))?;
+    if header.version > VERSION {
+        bail!("Session file version {} is newer than this bandwidthmon supports ({})", header.version, VERSION);
+    }
+
+    let mut frames = Vec::new();
+    for (n, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        match serde_json::from_str::<Frame>(&line) {
+            Ok(f)  => frames.push(f),
+            Err(_) => eprintln!("Warning: skipping malformed line {} in {}", n + 2, path.display()),
+        }
+    }
+    frames.retain(|f| f.stats.elapsed >= 0.001);
+    if frames.is_empty() {
+        bail!("{} contains no samples", path.display());
+    }
+    Ok((header, frames))
+}
+
+// ── Player ────────────────────────────────────────────────────────────────────
+
+struct Player {
+    frames:  Vec<Frame>,
+    monitor: NetworkMonitor,
+    pos:     usize,   // frames already fed to the monitor (≥ 1)
+    clock:   f64,     // virtual seconds accumulated towards frames[pos]
+    speed:   f64,
+    paused:  bool,
+}
+
+impl Player {
+    fn new(interface: String, frames: Vec<Frame>, history: usize, speed: f64) -> Self {
+        let mut p = Self {
+            frames,
+            monitor: NetworkMonitor::detached(interface, history),
+            pos:     0,
+            clock:   0.0,
+            speed:   speed.clamp(MIN_SPEED, MAX_SPEED),
+            paused:  false,
+        };
+        p.seek(1);
+        p
+    }
+
+    /// Jump to `target` frames played, rebuilding history when going back.
+    fn seek(&mut self, target: usize) {
+        let target = target.clamp(1, self.frames.len());
+        if target < self.pos {
+            let (iface, size) = (self.monitor.interface.clone(), self.monitor.history_size);
+            self.monitor = NetworkMonitor::detached(iface, size);
+            self.pos = 0;
+        }
+        while self.pos < target {
+            self.monitor.push(&self.frames[self.pos].stats, self.frames[self.pos].t);
+            self.pos += 1;
+        }
+        self.clock = 0.0;
+        if self.at_end() { self.paused = true; }
+    }
+
+    /// Advance the virtual clock by `real` seconds. True if a frame was played.
+    fn tick(&mut self, real: f64) -> bool {
+        if self.paused || self.at_end() { return false; }
+        self.clock += real * self.speed;
+        let mut played = false;
+        while !self.at_end() && self.clock >= self.frames[self.pos].stats.elapsed {
+            self.clock -= self.frames[self.pos].stats.elapsed;
+            self.monitor.push(&self.frames[self.pos].stats, self.frames[self.pos].t);
+            self.pos += 1;
+            played = true;
+        }
+        if self.at_end() { self.paused = true; }
+        played
+    }
+
+    fn at_end(&self) -> bool { self.pos >= self.frames.len() }
+
+    fn current(&self) -> &Frame { &self.frames[self.pos - 1] }
+
+    fn status(&self) -> String {
+        let when = DateTime::from_timestamp_millis(self.current().t)
+            .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let state = if self.at_end() { "end" } else if self.paused { "paused" } else { "playing" };
+        format!("{} {}x  {}  {}/{}  space:pause ←/→:seek +/-:speed q:quit",
+            state, self.speed, when, self.pos, self.frames.len())
+    }
+}
+
+// ── Entry ─────────────────────────────────────────────────────────────────────
+
+pub fn replay(r: &ReplayArgs, args: &Args) -> Result<()> {
+    if r.speed.is_nan() || r.speed <= 0.0 {
+        bail!("--speed must be greater than 0");
+    }
+    if !std::io::stdout().is_terminal() {
+        bail!("replay draws the live charts and needs a terminal on stdout");
+    }
+    let (header, frames) = load(&r.file)?;
+    let mut player = Player::new(header.interface, frames, args.history, r.speed);
+
+    let running = Arc::new(AtomicBool::new(true));
+    let rf = running.clone();
+    ctrlc::set_handler(move || { rf.store(false, Ordering::SeqCst); })?;
+
+    crate::clear_screen();
+    crate::flush();
+    enable_raw_mode()?;
+
+    let result: Result<()> = (|| {
+        let mut last = Instant::now();
+        let mut dirty = true;
+        while running.load(Ordering::SeqCst) {
+            if dirty {
+                render_frame(&player.monitor, &player.current().stats, args, Some(&player.status()), Inspect::default());
+                dirty = false;
+            }
+
+            if event::poll(Duration::from_millis(50))? {
+                if let Event::Key(k) = event::read()? {
+                    let pos = player.pos;
+                    match k.code {
+                        KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => break,
+                        KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => break,
+                        KeyCode::Char(' ') => {
+                            // Play again from the top once the end is reached.
+                            if player.at_end() { player.seek(1); }
+                            player.paused = !player.paused;
+                        }
+                        KeyCode::Right    => player.seek(pos + SEEK_SHORT),
+                        KeyCode::Left     => player.seek(pos.saturating_sub(SEEK_SHORT)),
+                        KeyCode::PageDown => player.seek(pos + SEEK_LONG),
+                        KeyCode::PageUp   => player.seek(pos.saturating_sub(SEEK_LONG)),
+                        KeyCode::Home     => player.seek(1),
+                        KeyCode::End      => player.seek(player.frames.len()),
+                        KeyCode::Char('+') | KeyCode::Char('=') => player.speed = (player.speed * 2.0).min(MAX_SPEED),
+                        KeyCode::Char('-') => player.speed = (player.speed / 2.0).max(MIN_SPEED),
+                        _ => continue,
+                    }
+                    dirty = true;
+                }
+            }
+
+            let now = Instant::now();
+            if player.tick((now - last).as_secs_f64()) { dirty = true; }
+            last = now;
+        }
+        Ok(())
+    })();
+
+    let _ = disable_raw_mode();
+    println!("\n\n{}", styled("Replay finished.", C_GREEN, true));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bandwidthmon-test-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    fn stats(download_bps: f64, elapsed: f64) -> BandwidthStats {
+        BandwidthStats { download_bps, rx_delta: (download_bps * elapsed) as u64, elapsed, ..BandwidthStats::default() }
+    }
+
+    fn frames(n: usize, elapsed: f64) -> Vec<Frame> {
+        (0..n).map(|i| Frame { t: 1_760_770_800_000 + i as i64 * 1000, stats: stats(i as f64 * 100.0, elapsed) }).collect()
+    }
+
+    fn load_err(path: &Path) -> String {
+        load(path).err().map(|e| format!("{:#}", e)).unwrap_or_default()
+    }
+
+    #[test]
+    fn sessions_round_trip() {
+        let path = temp_file("round-trip");
+        let mut w = SessionWriter::create(&path, "eth0").unwrap();
+        w.write(&[("lo".to_string(), stats(1.0, 1.0)), ("eth0".to_string(), stats(1000.0, 1.0))]).unwrap();
+        w.write(&[("eth0".to_string(), stats(5.0, 0.0))]).unwrap();   // too short to keep
+        w.rename("eth0", "lan0");
+        w.write(&[("lan0".to_string(), stats(2000.0, 0.5))]).unwrap();
+        drop(w);
+
+        let (header, frames) = load(&path).unwrap();
+        assert_eq!((header.format.as_str(), header.version, header.interface.as_str()), (FORMAT, VERSION, "eth0"));
+        let got: Vec<_> = frames.iter().map(|f| (f.stats.download_bps, f.stats.rx_delta, f.stats.elapsed)).collect();
+        assert_eq!(got, [(1000.0, 1000, 1.0), (2000.0, 1000, 0.5)]);
+        assert!(frames[0].t <= frames[1].t);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_last_line_is_skipped() {
+        let path = temp_file("torn");
+        let mut w = SessionWriter::create(&path, "eth0").unwrap();
+        w.write(&[("eth0".to_string(), stats(1000.0, 1.0))]).unwrap();
+        drop(w);
+        let mut text = fs::read_to_string(&path).unwrap();
+        text.push_str(r#"{"t":1760770801000,"download_bps":12"#);
+        fs::write(&path, text).unwrap();
+
+        let (_, frames) = load(&path).unwrap();
+        assert_eq!(frames.len(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn bad_headers_are_errors() {
+        let path = temp_file("headers");
+        let header = |version: u32| format!(r#"{{"format":"{}","version":{},"interface":"eth0","started":""}}"#, FORMAT, version);
+        let frame = r#"{"t":0,"download_bps":1.0,"upload_bps":0.0,"total_rx":0,"total_tx":0,"rx_delta":1,"tx_delta":0,"elapsed":1.0,"rx_packets":0,"tx_packets":0,"rx_errors":0,"tx_errors":0}"#;
+
+        fs::write(&path, format!("{}\n{}\n", header(VERSION), frame)).unwrap();
+        assert_eq!(load(&path).unwrap().1.len(), 1);
+
+        fs::write(&path, format!("{}\n{}\n", header(VERSION + 1), frame)).unwrap();
+        assert!(load_err(&path).contains("version 2 is newer"), "{}", load_err(&path));
+
+        fs::write(&path, format!("{}\n", frame)).unwrap();
+        assert!(load_err(&path).contains("not a bandwidthmon session file"));
+        fs::write(&path, "").unwrap();
+        assert!(load_err(&path).contains("not a bandwidthmon session file"));
+
+        fs::write(&path, format!("{}\n", header(VERSION))).unwrap();
+        assert!(load_err(&path).contains("contains no samples"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn player_ticks_on_virtual_time() {
+        let mut p = Player::new("eth0".to_string(), frames(5, 1.0), 60, 2.0);
+        assert_eq!((p.pos, p.paused), (1, false));
+
+        assert!(!p.tick(0.4));                 // 0.8 virtual seconds
+        assert!(p.tick(0.2));                  // 1.2: one frame, 0.2 carried over
+        assert_eq!(p.pos, 2);
+        assert!((p.clock - 0.2).abs() < 1e-9);
+        assert!(p.tick(1.0));                  // 2.2: two more
+        assert_eq!(p.pos, 4);
+
+        p.paused = true;
+        assert!(!p.tick(10.0));
+        assert_eq!(p.pos, 4);
+
+        p.paused = false;
+        assert!(p.tick(10.0));                 // runs off the end and stops there
+        assert!(p.at_end() && p.paused);
+        assert!(!p.tick(10.0));
+        assert_eq!(p.current().stats.download_bps, 400.0);
+        assert_eq!(p.monitor.track.sample_count, 5);
+    }
+
+    #[test]
+    fn player_seeks_and_rebuilds_history() {
+        let mut p = Player::new("eth0".to_string(), frames(100, 1.0), 60, 1000.0);
+        assert_eq!(p.speed, MAX_SPEED);
+        assert_eq!(Player::new("eth0".to_string(), frames(1, 1.0), 60, 0.0).speed, MIN_SPEED);
+
+        p.tick(0.5);
+        p.seek(p.pos + SEEK_LONG);
+        assert_eq!((p.pos, p.clock), (1 + 32 + SEEK_LONG, 0.0));
+        assert_eq!(p.monitor.track.sample_count, p.pos as u64);
+
+        // Backwards starts the monitor over, so history and peaks match.
+        p.seek(p.pos - SEEK_SHORT);
+        assert_eq!(p.pos, 83);
+        assert_eq!(p.monitor.track.sample_count, 83);
+        assert_eq!(p.monitor.track.history_dl.len(), 60);
+        assert_eq!(p.monitor.track.peak_dl, 8200.0);
+
+        p.seek(0);
+        assert_eq!((p.pos, p.monitor.track.sample_count), (1, 1));
+        p.seek(usize::MAX);
+        assert!(p.at_end() && p.paused);
+        assert_eq!(p.monitor.track.sample_count, 100);
+    }
+}
@@ -0,0 +1,247 @@
+// File: src/store.rs
+//! Persistent traffic history.
+//!
+//! Samples are appended to one tab-separated file per calendar month (UTC)
+//! under the data directory, one line per interval:
+//!
+//!   history-2026-10.tsv
+//!     <unix_secs> <TAB> <interface> <TAB> <rx_bytes> <TAB> <tx_bytes> <TAB> <secs>
+//!
+//! Tabs rather than spaces because Windows interface names contain spaces
+//! ("vEthernet (realtek)"). Plain text keeps the store greppable, survives a
+//! crash mid-write (a torn last line is simply skipped on load) and needs no
+//! extra dependency.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+const FILE_PREFIX: &str = "history-";
+const FILE_SUFFIX: &str = ".tsv";
+
+// ── Record ────────────────────────────────────────────────────────────────────
+
+/// One stored interval: bytes moved on `interface` during the `secs` seconds
+/// ending at `ts`.
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub ts:        i64,
+    pub interface: String,
+    pub rx:        u64,
+    pub tx:        u64,
+    pub secs:      f64,
+}
+
+impl Record {
+    fn to_line(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{:.3}\n", self.ts, self.interface, self.rx, self.tx, self.secs)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut f = line.split('\t');
+        let rec = Self {
+            ts:        f.next()?.parse().ok()?,
+            interface: f.next()?.to_string(),
+            rx:        f.next()?.parse().ok()?,
+            tx:        f.next()?.parse().ok()?,
+            secs:      f.next()?.trim_end().parse().ok()?,
+        };
+        if f.next().is_some() { return None; }
+        Some(rec)
+    }
+}
+
+// ── Paths ─────────────────────────────────────────────────────────────────────
+
+/// `$XDG_DATA_HOME/bandwidthmon`, `~/.local/share/bandwidthmon`, or
+/// `%LOCALAPPDATA%\bandwidthmon` on Windows. Falls back to `./bandwidthmon-data`.
+pub fn default_data_dir() -> PathBuf {
+    let base = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))
+    };
+    match base {
+        Some(b) => b.join("bandwidthmon"),
+        None    => PathBuf::from("bandwidthmon-data"),
+    }
+}
+
+/// "YYYY-MM" (UTC) of a unix timestamp — also the file name key.
+fn month_key(ts: i64) -> String {
+    DateTime::<Utc>::from_timestamp(ts, 0)
+        .unwrap_or_default()
+        .format("%Y-%m")
+        .to_string()
+}
+
+// ── Writer ────────────────────────────────────────────────────────────────────
+
+/// Append-only writer. Switches file when the month rolls over; every record
+/// is written with a single `write_all` so concurrent readers never see a
+/// half-merged line from two writers.
+pub struct HistoryStore {
+    dir:   PathBuf,
+    month: String,
+    file:  Option<File>,
+}
+
+impl HistoryStore {
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Cannot create data directory {}", dir.display()))?;
+        Ok(Self { dir: dir.to_path_buf(), month: String::new(), file: None })
+    }
+
+    pub fn append(&mut self, rec: &Record) -> Result<()> {
+        let month = month_key(rec.ts);
+        if self.file.is_none() || month != self.month {
+            let path = self.dir.join(format!("{}{}{}", FILE_PREFIX, month, FILE_SUFFIX));
+            let file = OpenOptions::new().create(true).append(true).open(&path)
+                .with_context(|| format!("Cannot open {}", path.display()))?;
+            self.file  = Some(file);
+            self.month = month;
+        }
+        if let Some(f) = self.file.as_mut() {
+            f.write_all(rec.to_line().as_bytes()).context("Cannot write history record")?;
+        }
+        Ok(())
+    }
+}
+
+// ── Reader ────────────────────────────────────────────────────────────────────
+
+/// Load every record with `from <= ts < to`, oldest first. Whole month files
+/// outside the range are skipped without being opened; malformed lines are
+/// ignored.
+pub fn load(dir: &Path, from: Option<i64>, to: Option<i64>) -> Result<Vec<Record>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Cannot read {}", dir.display())),
+    };
+
+    let lo = from.map(month_key);
+    let hi = to.map(|t| month_key(t - 1));
+
+    let mut files: Vec<(String, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            let month = name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?.to_string();
+            Some((month, e.path()))
+        })
+        .filter(|(m, _)| lo.as_ref().is_none_or(|lo| m >= lo) && hi.as_ref().is_none_or(|hi| m <= hi))
+        .collect();
+    files.sort();
+
+    let mut out = Vec::new();
+    for (_, path) in files {
+        let file = File::open(&path).with_context(|| format!("Cannot open {}", path.display()))?;
+        for line in BufReader::new(file).lines() {
+            let Ok(line) = line else { continue };
+            let Some(rec) = Record::parse(&line) else { continue };
+            if from.is_some_and(|f| rec.ts < f) || to.is_some_and(|t| rec.ts >= t) { continue; }
+            out.push(rec);
+        }
+    }
+    out.sort_by_key(|r| r.ts);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ts(y: i32, m: u32, d: u32) -> i64 {
+        Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap().timestamp()
+    }
+
+    fn rec(ts: i64, interface: &str, rx: u64) -> Record {
+        Record { ts, interface: interface.to_string(), rx, tx: rx / 2, secs: 1.0 }
+    }
+
+    fn fields(r: &Record) -> (i64, &str, u64, u64, f64) {
+        (r.ts, &r.interface, r.rx, r.tx, r.secs)
+    }
+
+    /// A fresh, empty data directory for one test.
+    fn data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bandwidthmon-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn month_file(dir: &Path, month: &str) -> PathBuf {
+        dir.join(format!("{}{}{}", FILE_PREFIX, month, FILE_SUFFIX))
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let r = Record { ts: 1_790_000_000, interface: "vEthernet (realtek)".to_string(), rx: 1234, tx: 5, secs: 0.25 };
+        let line = r.to_line();
+        assert_eq!(line, "1790000000\tvEthernet (realtek)\t1234\t5\t0.250\n");
+        assert_eq!(fields(&Record::parse(&line).unwrap()), fields(&r));
+        assert_eq!(fields(&Record::parse(line.trim_end()).unwrap()), fields(&r));
+
+        assert!(Record::parse("").is_none());
+        assert!(Record::parse("1790000000\teth0\t1\t2").is_none());
+        assert!(Record::parse("1790000000\teth0\t1\t2\t1.0\textra").is_none());
+        assert!(Record::parse("1790000000\teth0\t-1\t2\t1.0").is_none());
+        assert!(Record::parse("soon\teth0\t1\t2\t1.0").is_none());
+    }
+
+    #[test]
+    fn load_picks_month_files_by_name() {
+        let dir = data_dir("months");
+        let mut store = HistoryStore::open(&dir).unwrap();
+        for r in [rec(ts(2026, 9, 30), "eth0", 1), rec(ts(2026, 10, 1), "eth0", 2), rec(ts(2026, 11, 1), "eth0", 3)] {
+            store.append(&r).unwrap();
+        }
+        assert!(month_file(&dir, "2026-09").exists() && month_file(&dir, "2026-11").exists());
+        // A record filed under the wrong month is only seen if that file is read.
+        fs::write(month_file(&dir, "2026-08"), rec(ts(2026, 10, 15), "stray", 9).to_line()).unwrap();
+        fs::write(dir.join("notes.tsv"), rec(ts(2026, 10, 15), "notes", 9).to_line()).unwrap();
+
+        let all = load(&dir, None, None).unwrap();
+        let names: Vec<_> = all.iter().map(|r| (r.interface.as_str(), r.rx)).collect();
+        assert_eq!(names, [("eth0", 1), ("eth0", 2), ("stray", 9), ("eth0", 3)]);
+
+        let oct = load(&dir, Some(ts(2026, 10, 1) - 3600), Some(ts(2026, 10, 31))).unwrap();
+        assert_eq!(oct.iter().map(|r| r.rx).collect::<Vec<_>>(), [2]);
+
+        let from_sep = load(&dir, Some(ts(2026, 9, 1)), None).unwrap();
+        assert_eq!(from_sep.iter().map(|r| r.rx).collect::<Vec<_>>(), [1, 2, 3]);
+
+        assert!(load(&dir.join("missing"), None, None).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_skips_torn_lines() {
+        let dir = data_dir("torn");
+        let mut store = HistoryStore::open(&dir).unwrap();
+        store.append(&rec(ts(2026, 10, 1), "eth0", 10)).unwrap();
+        store.append(&rec(ts(2026, 10, 2), "eth0", 20)).unwrap();
+        drop(store);
+
+        // A crash mid-write leaves half a line with no newline.
+        let path = month_file(&dir, "2026-10");
+        let mut f = OpenOptions::new().append(true).open(&path).unwrap();
+        f.write_all(format!("{}\teth0\t3", ts(2026, 10, 3)).as_bytes()).unwrap();
+        drop(f);
+        assert_eq!(load(&dir, None, None).unwrap().iter().map(|r| r.rx).collect::<Vec<_>>(), [10, 20]);
+
+        // The next writer appends straight onto it: that merged line is lost
+        // too, but everything after it reads back.
+        let mut store = HistoryStore::open(&dir).unwrap();
+        store.append(&rec(ts(2026, 10, 4), "eth0", 40)).unwrap();
+        store.append(&rec(ts(2026, 10, 5), "eth0", 50)).unwrap();
+        assert_eq!(load(&dir, None, None).unwrap().iter().map(|r| r.rx).collect::<Vec<_>>(), [10, 20, 50]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+// File: src/textlog.rs
+//! The plain-text log of bandwidthmon2 and bandwidthmon3 when stdout is not
+//! a terminal: one colour-free line per interval, like `sar -n DEV`. Rates
+//! in kB/s, bytes moved during the interval in kB (1 kB = 1024 bytes) — the
+//! same columns as `bandwidthmon -o text`, which formats its rows here too.
+
+use anyhow::Result;
+use std::io::{stdout, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// One interval of the monitored interface.
+pub struct Line {
+    pub interface: String,
+    pub rx_bps:    f64,
+    pub tx_bps:    f64,
+    pub rx_bytes:  u64,
+    pub tx_bytes:  u64,
+}
+
+fn columns(time: &str, iface: &str, cols: [&str; 4]) -> String {
+    format!("{:<8}  {:<12} {:>12} {:>12} {:>12} {:>12}", time, iface, cols[0], cols[1], cols[2], cols[3])
+}
+
+pub fn header() -> String {
+    columns("TIME", "IFACE", ["rxkB/s", "txkB/s", "rxkB", "txkB"])
+}
+
+/// `l` under a wall-clock `time` (HH:MM:SS).
+pub fn row(time: &str, l: &Line) -> String {
+    let kb = |v: f64| format!("{:.2}", v / 1024.0);
+    columns(time, &l.interface, [&kb(l.rx_bps), &kb(l.tx_bps), &kb(l.rx_bytes as f64), &kb(l.tx_bytes as f64)])
+}
+
+/// Print a line from `sample` every `interval` until `running` clears.
+pub fn run(interval: Duration, running: &AtomicBool, mut sample: impl FnMut() -> Result<Line>) -> Result<()> {
+    let mut out = stdout().lock();
+
+    let result = (|| -> Result<()> {
+        writeln!(out, "{}", header())?;
+        out.flush()?;
+        let mut last_update = Instant::now();
+        while running.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(50));
+            if last_update.elapsed() < interval { continue; }
+            let l = sample()?;
+            last_update = Instant::now();
+            let time = chrono::Local::now().format("%H:%M:%S").to_string();
+            writeln!(out, "{}", row(&time, &l))?;
+            out.flush()?;
+        }
+        Ok(())
+    })();
+
+    // A closed pipe (e.g. `| head`) ends the log quietly.
+    match result {
+        Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == ErrorKind::BrokenPipe) => Ok(()),
+        r => r,
+    }
+}
@@ -0,0 +1,755 @@
+// File: src/view.rs
+//! Live key bindings for the chart view.
+//!
+//! Display toggles (`s`, `d`/`u`/`b`, `+`/`-`) edit `Args` in place, since
+//! `render_frame` already reads everything from there; the rest of the
+//! view's state (pause, help overlay, sampling interval) lives in `View`.
+//! Sampling and the sinks keep running while the display is paused.
+//!
+//! `i` opens the interface picker: every interface with its live rates,
+//! arrows to move, typing filters, Enter switches. The monitor keeps history
+//! for all interfaces, so switching back and forth loses nothing.
+//!
+//! `←`/`→` put a cursor on the charts and move it through the history buffer,
+//! scrolling back past the left edge; the status line shows the time and
+//! rates of the sample under it. New samples keep arriving but the window
+//! stays put until `End` returns to live.
+//!
+//! With mouse capture on (the default), hovering a chart puts the cursor
+//! under the pointer, the wheel zooms the time window in and out, clicking
+//! the title opens the picker and clicking a picker row switches to it.
+
+use crate::{clear_to_eol, flush, fmt_bps, procs, safe_plot_width, styled, term_cols, Args, NetworkMonitor,
+    C_CYAN, C_WHITE, C_YELLOW, INTERVAL, MAX_PLOT_WIDTH};
+use crate::capture::Group;
+use chrono::{DateTime, Local};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::Duration;
+
+/// Steps for `[` / `]`.
+const INTERVALS: &[Duration] = &[
+    Duration::from_millis(250), Duration::from_millis(500), Duration::from_secs(1),
+    Duration::from_secs(2), Duration::from_secs(5), Duration::from_secs(10),
+];
+
+/// Interfaces visible at once in the picker; the list scrolls past this.
+const PICKER_ROWS: usize = 12;
+
+/// Screen row of the first interface in the picker (below border, title, blank).
+const PICKER_TOP: usize = 5;
+
+/// Wheel zoom range: -4 packs 16 samples into a column, 3 widens a sample
+/// to 8 columns.
+const MIN_ZOOM: i32 = -4;
+const MAX_ZOOM: i32 = 3;
+
+/// Inner width of the overlay boxes.
+const BOX_WIDTH: usize = 56;
+
+const MIN_HEIGHT: usize = 2;
+const MAX_HEIGHT: usize = 60;
+
+const HELP: &[(&str, &str)] = &[
+    ("p",          "pause / resume display"),
+    ("r",          "reset peak, average and session totals"),
+    ("s",          "toggle summary"),
+    ("d u b",      "download / upload / both charts"),
+    ("+ -",        "taller / shorter charts"),
+    ("[ ]",        "faster / slower sampling"),
+    ("i",          "pick another interface"),
+    ("t",          "top processes table (Linux)"),
+    ("g",          "--capture: flows / remote hosts"),
+    ("← →",        "move the cursor through history"),
+    ("PgUp PgDn",  "scroll back / forward a screen"),
+    ("Home End",   "oldest sample / back to live"),
+    ("mouse",      "hover: inspect, wheel: zoom, click title: pick"),
+    ("?",          "show / hide this help"),
+    ("q Esc",      "quit (Esc leaves the cursor first)"),
+];
+
+/// What the loop should do after a key press or mouse event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action { Quit, Redraw, Ignore }
+
+/// Interface picker state: the typed filter and the highlighted row.
+struct Picker {
+    filter:   String,
+    selected: usize,
+}
+
+impl Picker {
+    /// Names matching the filter (case-insensitive substring), sorted.
+    fn matches<'a>(&self, monitor: &'a NetworkMonitor) -> Vec<&'a str> {
+        let f = self.filter.to_lowercase();
+        monitor.latest.iter()
+            .map(|(n, _)| n.as_str())
+            .filter(|n| n.to_lowercase().contains(&f))
+            .collect()
+    }
+
+    fn handle_key(&mut self, k: KeyEvent, monitor: &mut NetworkMonitor) -> PickerResult {
+        let count = self.matches(monitor).len();
+        match k.code {
+            KeyCode::Esc  => return PickerResult::Close,
+            KeyCode::Up   => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::PageUp   => self.selected = self.selected.saturating_sub(PICKER_ROWS),
+            KeyCode::PageDown => self.selected = (self.selected + PICKER_ROWS).min(count.saturating_sub(1)),
+            KeyCode::Enter => return self.choose(monitor),
+            KeyCode::Backspace => { self.filter.pop(); self.selected = 0; }
+            KeyCode::Char(c) => { self.filter.push(c); self.selected = 0; }
+            _ => {}
+        }
+        PickerResult::Stay
+    }
+
+    /// Wheel moves the highlight, a click on a row switches to it, a click
+    /// anywhere else closes the picker.
+    fn handle_mouse(&mut self, m: MouseEvent, monitor: &mut NetworkMonitor) -> PickerResult {
+        let count = self.matches(monitor).len();
+        match m.kind {
+            MouseEventKind::ScrollUp   => self.selected = self.selected.saturating_sub(1),
+            MouseEventKind::ScrollDown => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let visible = PICKER_ROWS.min(count).max(1);
+                let (row, col) = (m.row as usize, m.column as usize);
+                // Box: border at row PICKER_TOP - 3, footer two rows below the list.
+                if row < PICKER_TOP - 3 || row > PICKER_TOP + visible + 2 || !(2..BOX_WIDTH + 6).contains(&col) {
+                    return PickerResult::Close;
+                }
+                if (PICKER_TOP..PICKER_TOP + visible).contains(&row) && row - PICKER_TOP < count {
+                    self.selected = self.first() + row - PICKER_TOP;
+                    return self.choose(monitor);
+                }
+            }
+            _ => {}
+        }
+        PickerResult::Stay
+    }
+
+    /// Switch to the highlighted interface.
+    fn choose(&mut self, monitor: &mut NetworkMonitor) -> PickerResult {
+        match self.matches(monitor).get(self.selected).map(|n| n.to_string()) {
+            Some(name) => { monitor.switch(&name); PickerResult::Close }
+            None       => PickerResult::Stay,
+        }
+    }
+
+    /// First visible row — scrolled so the highlighted row stays visible.
+    fn first(&self) -> usize { self.selected.saturating_sub(PICKER_ROWS - 1) }
+
+    fn draw(&self, monitor: &NetworkMonitor) {
+        let names = self.matches(monitor);
+        let mut rows = vec![(format!("Interfaces   filter: {}_", self.filter), false), (String::new(), false)];
+        for (i, name) in names.iter().enumerate().skip(self.first()).take(PICKER_ROWS) {
+            let Some((_, s)) = monitor.latest.iter().find(|(n, _)| n == name) else { continue };
+            let cursor = if i == self.selected { '›' } else { ' ' };
+            let mark   = if *name == monitor.interface { '*' } else { ' ' };
+            let short: String = name.chars().take(18).collect();
+            rows.push((format!("{}{} {:<18} ↓{}  ↑{}", cursor, mark, short, fmt_bps(s.download_bps), fmt_bps(s.upload_bps)),
+                i == self.selected));
+        }
+        if names.is_empty() { rows.push(("  (no interface matches)".to_string(), false)); }
+        rows.push((String::new(), false));
+        rows.push(("↑/↓ move  Enter switch  Esc close  type to filter".to_string(), false));
+        draw_box(&rows);
+    }
+}
+
+enum PickerResult { Stay, Close }
+
+/// Which stretch of history the charts show: the window ending `scroll`
+/// samples before the newest, the cursor column inside it, if any, and the
+/// wheel zoom level.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Inspect {
+    pub scroll: usize,
+    pub cursor: Option<usize>,
+    pub zoom:   i32,
+}
+
+/// Where `render_frame` put a chart: its screen rows and the axis column.
+#[derive(Clone, Debug)]
+pub struct ChartArea {
+    pub rows: Range<usize>,
+    pub axis: usize,
+}
+
+pub struct View {
+    pub paused:   bool,
+    pub help:     bool,
+    pub interval: Duration,
+    pub inspect:  Inspect,
+    pub charts:   Vec<ChartArea>,  // from the last frame, for the mouse
+    hover:        bool,            // cursor follows the mouse, not the keys
+    picker:       Option<Picker>,
+}
+
+impl View {
+    pub fn new() -> Self {
+        Self {
+            paused:   false,
+            help:     false,
+            interval: INTERVAL,
+            inspect:  Inspect::default(),
+            charts:   Vec::new(),
+            hover:    false,
+            picker:   None,
+        }
+    }
+
+    /// Back to the live edge, keeping the zoom.
+    fn live(&mut self) {
+        self.inspect = Inspect { zoom: self.inspect.zoom, ..Inspect::default() };
+        self.hover = false;
+    }
+
+    pub fn handle_key(&mut self, k: KeyEvent, args: &mut Args, monitor: &mut NetworkMonitor) -> Action {
+        if k.code == KeyCode::Char('c') && k.modifiers.contains(KeyModifiers::CONTROL) {
+            return Action::Quit;
+        }
+        // The picker owns the keyboard while open — letters go to the filter.
+        if let Some(ref mut p) = self.picker {
+            if let PickerResult::Close = p.handle_key(k, monitor) { self.close_picker(); }
+            return Action::Redraw;
+        }
+        // Any key closes the help overlay; `?` and quit keys keep their meaning.
+        if self.help && !matches!(k.code, KeyCode::Char('?' | 'q' | 'Q') | KeyCode::Esc) {
+            self.help = false;
+            return Action::Redraw;
+        }
+        let w = self.window(args, monitor);
+        let ins = &mut self.inspect;
+        match k.code {
+            // Esc leaves the cursor first, then quits.
+            KeyCode::Esc if ins.cursor.is_some() => self.live(),
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Left if w.cols > 0 => match ins.cursor {
+                None                            => ins.cursor = Some(w.cols - w.step()),
+                Some(c) if c >= w.step()        => ins.cursor = Some(c - w.step()),
+                Some(_)                         => ins.scroll = (ins.scroll + w.per_col()).min(w.max_scroll),
+            },
+            KeyCode::Right => match ins.cursor {
+                None                            => return Action::Ignore,
+                Some(c) if c + w.step() < w.cols => ins.cursor = Some(c + w.step()),
+                Some(_)                         => ins.scroll = ins.scroll.saturating_sub(w.per_col()),
+            },
+            KeyCode::PageUp if w.cols > 0 => {
+                ins.cursor.get_or_insert(w.cols - 1);
+                ins.scroll = (ins.scroll + w.span).min(w.max_scroll);
+            }
+            KeyCode::PageDown if ins.cursor.is_some() => ins.scroll = ins.scroll.saturating_sub(w.span),
+            KeyCode::Home if w.cols > 0 => { ins.scroll = w.max_scroll; ins.cursor = Some(0); }
+            KeyCode::End => self.live(),
+            KeyCode::Char(c) => if !self.toggle(c, args, monitor) { return Action::Ignore; },
+            _ => return Action::Ignore,
+        }
+        // Keys take the cursor over from the mouse.
+        if matches!(k.code, KeyCode::Left | KeyCode::Right | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home) {
+            self.hover = false;
+        }
+        Action::Redraw
+    }
+
+    /// Display toggles and settings: everything a plain letter does besides
+    /// quitting. False if `c` means nothing here.
+    fn toggle(&mut self, c: char, args: &mut Args, monitor: &mut NetworkMonitor) -> bool {
+        match c {
+            'p' | ' ' => self.paused = !self.paused,
+            'r'       => monitor.reset_stats(),
+            's'       => args.summary = !args.summary,
+            'd'       => { args.download = true;  args.upload = false; }
+            'u'       => { args.download = false; args.upload = true;  }
+            'b'       => { args.download = false; args.upload = false; }
+            '+' | '=' => args.height = (args.height + 1).min(MAX_HEIGHT),
+            '-'       => args.height = args.height.saturating_sub(1).max(MIN_HEIGHT),
+            '['       => self.step_interval(-1),
+            ']'       => self.step_interval(1),
+            '?'       => self.help = !self.help,
+            'i'       => self.open_picker(monitor),
+            'g' if args.capture.is_some() => {
+                args.group = match args.group { Group::Flow => Group::Host, Group::Host => Group::Flow };
+            }
+            // Sockets of another namespace aren't visible to the table.
+            't' if cfg!(target_os = "linux") && args.namespace().is_none() => {
+                args.processes = match args.processes { Some(_) => None, None => Some(procs::DEFAULT_TOP) };
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn handle_mouse(&mut self, m: MouseEvent, args: &Args, monitor: &mut NetworkMonitor) -> Action {
+        if let Some(ref mut p) = self.picker {
+            if let PickerResult::Close = p.handle_mouse(m, monitor) { self.close_picker(); }
+            return Action::Redraw;
+        }
+        if self.help {
+            if let MouseEventKind::Down(_) = m.kind { self.help = false; return Action::Redraw; }
+            return Action::Ignore;
+        }
+        let (row, col) = (m.row as usize, m.column as usize);
+        match m.kind {
+            MouseEventKind::Moved | MouseEventKind::Drag(_) => {
+                let w = self.window(args, monitor);
+                let hit = self.charts.iter()
+                    .find(|a| a.rows.contains(&row) && col > a.axis && col - a.axis - 1 < w.cols);
+                match hit {
+                    Some(a) => { self.inspect.cursor = Some(col - a.axis - 1); self.hover = true; }
+                    None if self.hover => self.live(),
+                    None => return Action::Ignore,
+                }
+            }
+            MouseEventKind::ScrollUp   => self.inspect.zoom = (self.inspect.zoom + 1).min(MAX_ZOOM),
+            MouseEventKind::ScrollDown => self.inspect.zoom = (self.inspect.zoom - 1).max(MIN_ZOOM),
+            // The title line: pick another interface.
+            MouseEventKind::Down(MouseButton::Left) if row == 0 => self.open_picker(monitor),
+            _ => return Action::Ignore,
+        }
+        // Zooming changes the column count; keep the cursor on the chart.
+        let w = self.window(args, monitor);
+        if let Some(ref mut c) = self.inspect.cursor { *c = (*c).min(w.cols.saturating_sub(1)); }
+        Action::Redraw
+    }
+
+    fn open_picker(&mut self, monitor: &NetworkMonitor) {
+        let selected = monitor.latest.iter().position(|(n, _)| n == &monitor.interface).unwrap_or(0);
+        self.picker = Some(Picker { filter: String::new(), selected });
+    }
+
+    /// Open the picker on the interfaces containing an ambiguous `-i`.
+    pub fn open_chooser(&mut self, pattern: String) {
+        self.picker = Some(Picker { filter: pattern, selected: 0 });
+    }
+
+    fn close_picker(&mut self) {
+        self.picker = None;
+        self.live();  // possibly another interface's history now
+    }
+
+    /// Move to the next shorter / longer interval in `INTERVALS`.
+    fn step_interval(&mut self, dir: i32) {
+        let i = INTERVALS.iter().position(|d| *d >= self.interval).unwrap_or(INTERVALS.len() - 1);
+        let i = if dir < 0 { i.saturating_sub(1) } else { (i + 1).min(INTERVALS.len() - 1) };
+        self.interval = INTERVALS[i];
+    }
+
+    /// The chart window as `render_frame` will draw it.
+    pub fn window(&self, args: &Args, monitor: &NetworkMonitor) -> Window {
+        Window::new(monitor.track.history_dl.len(), args, self.inspect)
+    }
+
+    /// A sample arrived: while inspecting, scroll with it so the window and
+    /// cursor stay on the same samples.
+    pub fn on_sample(&mut self, args: &Args, monitor: &NetworkMonitor) {
+        if self.inspect.cursor.is_none() { return; }
+        let w = self.window(args, monitor);
+        self.inspect.scroll = (self.inspect.scroll + 1).min(w.max_scroll);
+        if let Some(ref mut c) = self.inspect.cursor { *c = (*c).min(w.cols.saturating_sub(1)); }
+    }
+
+    /// Text for the grey hint after the current speeds — the sample under
+    /// the cursor while inspecting, or the peak of the samples a zoomed-out
+    /// column covers.
+    pub fn status(&self, args: &Args, monitor: &NetworkMonitor) -> String {
+        let w = self.window(args, monitor);
+        let t = &monitor.track;
+        if let Some(r) = self.inspect.cursor.map(|c| w.samples(c)).filter(|r| r.end <= t.history_ts.len()) {
+            let when = |i: usize| DateTime::from_timestamp_millis(t.history_ts[i])
+                .map(|d| d.with_timezone(&Local).format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            let peak = |h: &VecDeque<f64>| h.range(r.clone()).copied().fold(0.0, f64::max);
+            let (dl, ul) = (peak(&t.history_dl), peak(&t.history_ul));
+            let back = t.history_ts.len() - r.end;
+            let hint = if self.hover { "" } else { " — ←/→ move  End live" };
+            return if r.len() > 1 {
+                format!("▲ {}–{}  peak ↓{}  ↑{}  [-{}]{}", when(r.start), when(r.end - 1), fmt_bps(dl), fmt_bps(ul), back, hint)
+            } else {
+                format!("▲ {}  ↓{}  ↑{}  [-{}]{}", when(r.start), fmt_bps(dl), fmt_bps(ul), back, hint)
+            };
+        }
+        let mut every = format!("every {}s", self.interval.as_secs_f64());
+        if let Some(z) = w.zoom_label() { every = format!("{}, {}", every, z); }
+        if self.paused {
+            format!("PAUSED ({}) — 'p' resume  '?' help", every)
+        } else {
+            format!("{} — '?' help  'q' quit", every)
+        }
+    }
+
+    /// Draw the help or picker overlay, if open, over the frame just rendered.
+    pub fn draw_overlay(&self, monitor: &NetworkMonitor) {
+        if self.help {
+            let mut rows = vec![("Keys".to_string(), false)];
+            rows.extend(HELP.iter().map(|(keys, what)| (format!("{:<11}{}", keys, what), false)));
+            rows.push((String::new(), false));
+            rows.push(("any key closes this help".to_string(), false));
+            draw_box(&rows);
+        } else if let Some(ref p) = self.picker {
+            p.draw(monitor);
+        }
+    }
+}
+
+// ── Chart window ──────────────────────────────────────────────────────────────
+
+/// The stretch of history one chart shows and how samples map to columns:
+/// zoomed in, each sample is repeated over several columns; zoomed out, each
+/// column holds the peak of several samples so short spikes stay visible.
+#[derive(Copy, Clone, Debug)]
+pub struct Window {
+    first:      usize,   // history index of the first sample shown
+    span:       usize,   // samples shown
+    cols:       usize,   // chart columns
+    max_scroll: usize,
+    zoom:       i32,
+}
+
+impl Window {
+    /// `len` samples of history, on a terminal as wide as it is right now.
+    pub fn new(len: usize, args: &Args, inspect: Inspect) -> Self {
+        Self::within(len, budget(args), inspect)
+    }
+
+    /// Zoomed out just far enough to show all `len` samples at once.
+    pub fn fit(len: usize, args: &Args) -> Self {
+        Self::fit_within(len, budget(args))
+    }
+
+    /// `len` samples in at most `budget` columns.
+    fn within(len: usize, budget: usize, inspect: Inspect) -> Self {
+        let k = 1usize << inspect.zoom.unsigned_abs();
+        let fits = if inspect.zoom >= 0 { (budget / k).max(1) } else { budget * k };
+        let span = fits.min(len);
+        let max_scroll = len - span;
+        let first = max_scroll - inspect.scroll.min(max_scroll);
+        let cols = if inspect.zoom >= 0 { span * k } else { span.div_ceil(k) };
+        Self { first, span, cols, max_scroll, zoom: inspect.zoom }
+    }
+
+    fn fit_within(len: usize, budget: usize) -> Self {
+        let mut zoom = 0;
+        while budget << -zoom < len { zoom -= 1; }
+        Self::within(len, budget, Inspect { zoom, ..Inspect::default() })
+    }
+
+    /// Columns per sample (zoomed in).
+    fn step(&self) -> usize { if self.zoom > 0 { 1 << self.zoom } else { 1 } }
+
+    /// Samples per column (zoomed out).
+    pub fn per_col(&self) -> usize { if self.zoom < 0 { 1 << -self.zoom } else { 1 } }
+
+    /// One value per column.
+    pub fn series(&self, history: &VecDeque<f64>) -> Vec<f64> {
+        let shown: Vec<f64> = history.range(self.first..self.first + self.span).copied().collect();
+        if self.zoom >= 0 {
+            shown.iter().flat_map(|v| std::iter::repeat_n(*v, self.step())).collect()
+        } else {
+            shown.chunks(self.per_col()).map(|c| c.iter().copied().fold(0.0, f64::max)).collect()
+        }
+    }
+
+    /// History indices under column `col`.
+    fn samples(&self, col: usize) -> Range<usize> {
+        let start = self.first + col / self.step() * self.per_col();
+        start..(start + self.per_col()).min(self.first + self.span)
+    }
+
+    fn zoom_label(&self) -> Option<String> {
+        match self.zoom {
+            0          => None,
+            z if z > 0 => Some(format!("zoom ×{}", self.step())),
+            _          => Some(format!("zoom ÷{}", self.per_col())),
+        }
+    }
+}
+
+/// Chart columns available on the terminal.
+fn budget(args: &Args) -> usize {
+    safe_plot_width(args.width, MAX_PLOT_WIDTH, term_cols())
+}
+
+/// A framed box just below the header and speeds lines; `(text, highlight)`
+/// per row. Rows start with `\r` because raw mode may leave the cursor
+/// mid-line after a newline.
+fn draw_box(rows: &[(String, bool)]) {
+    let edge = "─".repeat(BOX_WIDTH + 2);
+    print!("\x1B[3;1H");
+    print!("\r  {}", styled(&format!("┌{}┐", edge), C_CYAN, false));
+    clear_to_eol(); println!();
+    for (text, hl) in rows {
+        let pad = BOX_WIDTH.saturating_sub(text.chars().count());
+        let body: String = text.chars().take(BOX_WIDTH).collect::<String>() + &" ".repeat(pad);
+        print!("\r  {}{}{}",
+            styled("│ ", C_CYAN, false),
+            styled(&body, if *hl { C_YELLOW } else { C_WHITE }, *hl),
+            styled(" │", C_CYAN, false));
+        clear_to_eol(); println!();
+    }
+    print!("\r  {}", styled(&format!("└{}┘", edge), C_CYAN, false));
+    clear_to_eol(); println!();
+    flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BandwidthStats;
+    use clap::Parser;
+
+    fn args(argv: &[&str]) -> Args {
+        Args::try_parse_from(std::iter::once("bandwidthmon").chain(argv.iter().copied())).unwrap()
+    }
+
+    fn monitor() -> NetworkMonitor {
+        let mut m = NetworkMonitor::detached("eth0".to_string(), 60);
+        for i in 1..=3 {
+            m.push(&BandwidthStats { download_bps: i as f64 * 100.0, rx_delta: 100, elapsed: 1.0, ..BandwidthStats::default() }, i);
+        }
+        m
+    }
+
+    fn key(code: KeyCode) -> KeyEvent { KeyEvent::new(code, KeyModifiers::NONE) }
+
+    fn press(view: &mut View, args: &mut Args, monitor: &mut NetworkMonitor, c: char) -> Action {
+        view.handle_key(key(KeyCode::Char(c)), args, monitor)
+    }
+
+    #[test]
+    fn interval_steps_clamp_at_both_ends() {
+        let mut v = View::new();
+        assert_eq!(v.interval, Duration::from_secs(1));
+        v.step_interval(-1);
+        assert_eq!(v.interval, Duration::from_millis(500));
+        v.step_interval(-1);
+        v.step_interval(-1);
+        assert_eq!(v.interval, INTERVALS[0]);
+        for _ in 0..10 { v.step_interval(1); }
+        assert_eq!(v.interval, Duration::from_secs(10));
+        v.step_interval(-1);
+        assert_eq!(v.interval, Duration::from_secs(5));
+
+        let (mut a, mut m) = (args(&[]), monitor());
+        assert_eq!(press(&mut v, &mut a, &mut m, ']'), Action::Redraw);
+        assert_eq!(v.interval, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn reset_keeps_history() {
+        let (mut v, mut a, mut m) = (View::new(), args(&[]), monitor());
+        assert_eq!(m.track.peak_dl, 300.0);
+        assert_eq!(press(&mut v, &mut a, &mut m, 'r'), Action::Redraw);
+        assert_eq!((m.track.peak_dl, m.track.sample_count, m.track.session_rx), (0.0, 0, 0));
+        assert_eq!(m.track.history_dl.len(), 3);
+    }
+
+    #[test]
+    fn toggles() {
+        let (mut v, mut a, mut m) = (View::new(), args(&["-H", "3"]), monitor());
+        let summary = a.summary;
+        press(&mut v, &mut a, &mut m, 's');
+        assert_eq!(a.summary, !summary);
+        press(&mut v, &mut a, &mut m, ' ');
+        assert!(v.paused);
+        press(&mut v, &mut a, &mut m, 'p');
+        assert!(!v.paused);
+
+        press(&mut v, &mut a, &mut m, 'd');
+        assert_eq!((a.download, a.upload), (true, false));
+        press(&mut v, &mut a, &mut m, 'u');
+        assert_eq!((a.download, a.upload), (false, true));
+        press(&mut v, &mut a, &mut m, 'b');
+        assert_eq!((a.download, a.upload), (false, false));
+
+        for _ in 0..5 { press(&mut v, &mut a, &mut m, '-'); }
+        assert_eq!(a.height, MIN_HEIGHT);
+        for _ in 0..100 { press(&mut v, &mut a, &mut m, '='); }
+        assert_eq!(a.height, MAX_HEIGHT);
+
+        // `g` only means something with --capture.
+        assert_eq!(press(&mut v, &mut a, &mut m, 'g'), Action::Ignore);
+        let mut a = args(&["--capture", "5"]);
+        press(&mut v, &mut a, &mut m, 'g');
+        assert_eq!(a.group, Group::Host);
+        press(&mut v, &mut a, &mut m, 'g');
+        assert_eq!(a.group, Group::Flow);
+
+        assert_eq!(press(&mut v, &mut a, &mut m, 'z'), Action::Ignore);
+    }
+
+    #[test]
+    fn help_and_quit_keys() {
+        let (mut v, mut a, mut m) = (View::new(), args(&[]), monitor());
+        press(&mut v, &mut a, &mut m, '?');
+        assert!(v.help);
+        // Any other key only closes the help.
+        assert_eq!(press(&mut v, &mut a, &mut m, 's'), Action::Redraw);
+        assert!(!v.help && a.summary == args(&[]).summary);
+        assert_eq!(press(&mut v, &mut a, &mut m, 'q'), Action::Quit);
+        assert_eq!(v.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL), &mut a, &mut m), Action::Quit);
+
+        // Esc leaves the cursor before it quits.
+        v.inspect.cursor = Some(0);
+        assert_eq!(v.handle_key(key(KeyCode::Esc), &mut a, &mut m), Action::Redraw);
+        assert!(v.inspect.cursor.is_none());
+        assert_eq!(v.handle_key(key(KeyCode::Esc), &mut a, &mut m), Action::Quit);
+    }
+
+    fn history(len: usize) -> VecDeque<f64> { (0..len).map(|i| i as f64).collect() }
+
+    fn at(len: usize, budget: usize, scroll: usize, zoom: i32) -> Window {
+        Window::within(len, budget, Inspect { scroll, cursor: None, zoom })
+    }
+
+    #[test]
+    fn window_scrolls_within_history() {
+        let h = history(100);
+        let w = at(100, 40, 0, 0);
+        assert_eq!((w.first, w.span, w.cols, w.max_scroll), (60, 40, 40, 60));
+        assert_eq!(w.series(&h), (60..100).map(|i| i as f64).collect::<Vec<_>>());
+        assert_eq!(w.samples(0), 60..61);
+
+        assert_eq!(at(100, 40, 10, 0).first, 50);
+        // Scrolling stops at the oldest sample.
+        let w = at(100, 40, 1000, 0);
+        assert_eq!((w.first, w.series(&h)[0]), (0, 0.0));
+
+        // Less history than columns: all of it, nothing to scroll.
+        let w = at(10, 40, 5, 0);
+        assert_eq!((w.first, w.span, w.cols, w.max_scroll), (0, 10, 10, 0));
+        let w = at(0, 40, 0, 0);
+        assert_eq!((w.cols, w.series(&history(0)).len()), (0, 0));
+    }
+
+    #[test]
+    fn zooming_in_repeats_samples() {
+        let h = history(100);
+        let w = at(100, 40, 0, 2);
+        assert_eq!((w.first, w.span, w.cols, w.step(), w.per_col()), (90, 10, 40, 4, 1));
+        let s = w.series(&h);
+        assert_eq!(s.len(), 40);
+        assert_eq!(&s[..5], &[90.0, 90.0, 90.0, 90.0, 91.0]);
+        assert_eq!(w.samples(5), 91..92);
+        assert_eq!(w.zoom_label().as_deref(), Some("zoom ×4"));
+        // Never less than one sample, however narrow.
+        assert_eq!(at(100, 3, 0, 3).span, 1);
+    }
+
+    #[test]
+    fn zooming_out_keeps_column_peaks() {
+        let mut h = history(100);
+        h[97] = 1000.0;
+        let w = at(100, 10, 0, -2);
+        assert_eq!((w.first, w.span, w.cols, w.step(), w.per_col()), (60, 40, 10, 1, 4));
+        let s = w.series(&h);
+        assert_eq!(s.len(), 10);
+        assert_eq!(s[0], 63.0);
+        assert_eq!(s[9], 1000.0);
+        assert_eq!(w.samples(9), 96..100);
+        assert_eq!(w.zoom_label().as_deref(), Some("zoom ÷4"));
+
+        // A short last column holds what is left.
+        let w = at(10, 40, 0, -2);
+        assert_eq!((w.span, w.cols), (10, 3));
+        assert_eq!(w.series(&history(10)), [3.0, 7.0, 9.0]);
+        assert_eq!(w.samples(2), 8..10);
+
+        // Scrolling moves by samples, clamped at the oldest.
+        assert_eq!(at(100, 10, 4, -2).first, 56);
+        assert_eq!(at(100, 10, 500, -2).first, 0);
+    }
+
+    #[test]
+    fn fit_zooms_out_until_everything_shows() {
+        let w = Window::fit_within(100, 40);
+        assert_eq!((w.zoom, w.first, w.span, w.cols), (-2, 0, 100, 25));
+        assert_eq!(Window::fit_within(40, 40).zoom, 0);
+        assert_eq!(Window::fit_within(41, 40).zoom, -1);
+        assert_eq!(Window::fit_within(0, 40).zoom, 0);
+    }
+
+    /// A monitor on eth0 whose last sample listed these interfaces, plus
+    /// the `eth*` group summed under its pattern.
+    fn picker_monitor() -> NetworkMonitor {
+        let mut m = NetworkMonitor::detached("eth0".to_string(), 60);
+        m.latest = ["docker0", "eth0", "eth1", "eth*", "lo", "wlan0"].iter()
+            .map(|n| (n.to_string(), BandwidthStats::default())).collect();
+        m
+    }
+
+    fn picker(filter: &str, selected: usize) -> Picker {
+        Picker { filter: filter.to_string(), selected }
+    }
+
+    #[test]
+    fn picker_filters() {
+        let m = picker_monitor();
+        assert_eq!(picker("", 0).matches(&m), ["docker0", "eth0", "eth1", "eth*", "lo", "wlan0"]);
+        assert_eq!(picker("ETH", 0).matches(&m), ["eth0", "eth1", "eth*"]);
+        assert_eq!(picker("*", 0).matches(&m), ["eth*"]);
+        assert!(picker("ppp", 0).matches(&m).is_empty());
+    }
+
+    #[test]
+    fn picker_selection() {
+        let mut m = picker_monitor();
+        let mut p = picker("", 0);
+        p.handle_key(key(KeyCode::Up), &mut m);
+        assert_eq!(p.selected, 0);
+        for _ in 0..10 { p.handle_key(key(KeyCode::Down), &mut m); }
+        assert_eq!(p.selected, 5);
+        p.handle_key(key(KeyCode::PageUp), &mut m);
+        assert_eq!(p.selected, 0);
+        p.handle_key(key(KeyCode::PageDown), &mut m);
+        assert_eq!(p.selected, 5);
+
+        // Typing filters and starts over at the top.
+        p.handle_key(key(KeyCode::Char('e')), &mut m);
+        p.handle_key(key(KeyCode::Char('t')), &mut m);
+        assert_eq!((p.filter.as_str(), p.selected), ("et", 0));
+        p.handle_key(key(KeyCode::Down), &mut m);
+        p.handle_key(key(KeyCode::Down), &mut m);
+        assert!(matches!(p.handle_key(key(KeyCode::Enter), &mut m), PickerResult::Close));
+        assert_eq!(m.interface, "eth*");
+        p.handle_key(key(KeyCode::Backspace), &mut m);
+        assert_eq!((p.filter.as_str(), p.selected), ("e", 0));
+        assert!(matches!(p.handle_key(key(KeyCode::Esc), &mut m), PickerResult::Close));
+    }
+
+    #[test]
+    fn picker_with_no_matches_stays_open() {
+        let mut m = picker_monitor();
+        let mut p = picker("ppp", 0);
+        p.handle_key(key(KeyCode::Down), &mut m);
+        assert_eq!(p.selected, 0);
+        assert!(matches!(p.handle_key(key(KeyCode::Enter), &mut m), PickerResult::Stay));
+        assert_eq!(m.interface, "eth0");
+        // An empty filter brings everything back; one Backspace too many is harmless.
+        for _ in 0..4 { p.handle_key(key(KeyCode::Backspace), &mut m); }
+        assert_eq!(p.filter, "");
+        assert_eq!(p.matches(&m).len(), 6);
+    }
+
+    #[test]
+    fn view_opens_the_picker_on_the_current_interface() {
+        let (mut v, mut a, mut m) = (View::new(), args(&[]), picker_monitor());
+        m.switch("lo");
+        press(&mut v, &mut a, &mut m, 'i');
+        assert_eq!(v.picker.as_ref().map(|p| p.selected), Some(4));
+        // Letters go to the filter while it is open, not to the toggles.
+        let summary = a.summary;
+        press(&mut v, &mut a, &mut m, 's');
+        assert_eq!((v.picker.as_ref().unwrap().filter.as_str(), a.summary), ("s", summary));
+        v.handle_key(key(KeyCode::Esc), &mut a, &mut m);
+        assert!(v.picker.is_none());
+
+        // An ambiguous -i opens it filtered; choosing one switches to it.
+        v.open_chooser("eth".to_string());
+        v.handle_key(key(KeyCode::Down), &mut a, &mut m);
+        assert_eq!(v.handle_key(key(KeyCode::Enter), &mut a, &mut m), Action::Redraw);
+        assert!(v.picker.is_none());
+        assert_eq!(m.interface, "eth1");
+    }
+}